@@ -2,16 +2,18 @@
 
 use std::collections::BTreeSet;
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use radicle::cob::common::{Label, Uri};
 use radicle::cob::store::CobAction;
 use radicle::cob::thread::CommentId;
-use radicle::cob::{Embed, ObjectId, Title};
+use radicle::cob::{Embed, EntryId, ObjectId, Title};
 use radicle::prelude::Did;
 
 use radicle::git::Oid;
 
+use crate::estimate::Estimate;
+use crate::milestone::{DueDate, MilestoneId};
 use crate::state::{PlanStatus, TaskId};
 
 /// Plan action. Represents all possible mutations to a plan's state.
@@ -52,6 +54,9 @@ pub enum Action {
     SetStatus {
         /// New status.
         status: PlanStatus,
+        /// Complete the plan even though not all tasks are done.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        force: bool,
     },
 
     /// Add a task to the plan.
@@ -62,9 +67,9 @@ pub enum Action {
         /// Optional detailed description.
         #[serde(default, skip_serializing_if = "Option::is_none")]
         description: Option<String>,
-        /// Optional time estimate (e.g., "2h", "1d").
+        /// Optional estimate (e.g., "2h", "1d", "3sp").
         #[serde(default, skip_serializing_if = "Option::is_none")]
-        estimate: Option<String>,
+        estimate: Option<Estimate>,
         /// Files affected by this task.
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         affected_files: Vec<String>,
@@ -83,7 +88,7 @@ pub enum Action {
         description: Option<Option<String>>,
         /// New estimate (if changing).
         #[serde(default, skip_serializing_if = "Option::is_none")]
-        estimate: Option<Option<String>>,
+        estimate: Option<Option<Estimate>>,
         /// New affected files (if changing).
         #[serde(default, skip_serializing_if = "Option::is_none")]
         affected_files: Option<Vec<String>>,
@@ -130,6 +135,61 @@ pub enum Action {
         blocked_by: Vec<TaskId>,
     },
 
+    /// Make a task a subtask of another, or a top-level task.
+    #[serde(rename = "task.setParent")]
+    SetTaskParent {
+        /// Task ID.
+        task_id: TaskId,
+        /// Parent task ID, or `None` for a top-level task.
+        parent: Option<TaskId>,
+    },
+
+    /// Add a task to a milestone, or remove it from its milestone.
+    #[serde(rename = "task.setMilestone")]
+    SetTaskMilestone {
+        /// Task ID.
+        task_id: TaskId,
+        /// Milestone ID, or `None` for no milestone.
+        milestone_id: Option<MilestoneId>,
+    },
+
+    /// Add a milestone to the plan.
+    #[serde(rename = "milestone.add")]
+    AddMilestone {
+        /// Milestone name.
+        name: String,
+        /// Optional due date.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        due: Option<DueDate>,
+    },
+
+    /// Edit an existing milestone.
+    #[serde(rename = "milestone.edit")]
+    EditMilestone {
+        /// Milestone ID to edit.
+        milestone_id: MilestoneId,
+        /// New name (if changing).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        /// New due date (if changing); `null` clears it.
+        #[serde(default, deserialize_with = "some", skip_serializing_if = "Option::is_none")]
+        due: Option<Option<DueDate>>,
+    },
+
+    /// Remove a milestone from the plan. Its tasks are kept, without a milestone.
+    #[serde(rename = "milestone.remove")]
+    RemoveMilestone {
+        /// Milestone ID to remove.
+        milestone_id: MilestoneId,
+    },
+
+    /// Reorder the plan's milestones.
+    #[serde(rename = "milestone.reorder")]
+    ReorderMilestones {
+        /// New milestone order (milestone IDs).
+        milestone_ids: Vec<MilestoneId>,
+    },
+
     /// Link a Radicle issue to the plan.
     #[serde(rename = "link.issue")]
     LinkIssue {
@@ -167,6 +227,29 @@ pub enum Action {
         issue_id: ObjectId,
     },
 
+    /// Link a task to the patch that implements it. The task is done once the patch
+    /// is merged.
+    #[serde(rename = "task.linkPatch")]
+    LinkTaskToPatch {
+        /// Task ID.
+        task_id: TaskId,
+        /// Patch to link.
+        patch_id: ObjectId,
+    },
+
+    /// Assign users to a task, or unassign them.
+    #[serde(rename = "task.assign")]
+    AssignTask {
+        /// Task ID.
+        task_id: TaskId,
+        /// Users to assign.
+        #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+        add: BTreeSet<Did>,
+        /// Users to unassign.
+        #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+        remove: BTreeSet<Did>,
+    },
+
     /// Add a critical file path.
     #[serde(rename = "criticalFile.add")]
     AddCriticalFile {
@@ -226,11 +309,60 @@ pub enum Action {
         /// New set of assignees.
         assignees: BTreeSet<Did>,
     },
+
+    /// Approve a revision of the plan.
+    #[serde(rename = "plan.approve")]
+    Approve {
+        /// Revision being approved: the last change to the plan's content.
+        revision: EntryId,
+    },
+
+    /// Withdraw an approval of the plan.
+    #[serde(rename = "plan.unapprove")]
+    Unapprove,
+}
+
+impl Action {
+    /// Whether the action changes the plan's content, ie. what approvals are given
+    /// for: its title, description, tasks, milestones and critical files.
+    pub fn is_revision(&self) -> bool {
+        matches!(
+            self,
+            Self::Open { .. }
+                | Self::EditTitle { .. }
+                | Self::EditDescription { .. }
+                | Self::AddTask { .. }
+                | Self::EditTask { .. }
+                | Self::RemoveTask { .. }
+                | Self::ReorderTasks { .. }
+                | Self::SetTaskBlockedBy { .. }
+                | Self::SetTaskParent { .. }
+                | Self::SetTaskMilestone { .. }
+                | Self::AddMilestone { .. }
+                | Self::EditMilestone { .. }
+                | Self::RemoveMilestone { .. }
+                | Self::ReorderMilestones { .. }
+                | Self::AddCriticalFile { .. }
+                | Self::RemoveCriticalFile { .. }
+        )
+    }
+}
+
+/// Deserialize a present field as `Some`, so that an explicit `null` is kept apart
+/// from a missing field.
+fn some<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
 }
 
 impl CobAction for Action {
     fn produces_identifier(&self) -> bool {
-        matches!(self, Self::Comment { .. } | Self::AddTask { .. })
+        // Tasks and milestones are identified by their index within the change (see
+        // `task_id` and `milestone_id`), so several can be created by a single change.
+        matches!(self, Self::Comment { .. })
     }
 }
 
@@ -277,6 +409,18 @@ mod tests {
         assert!(matches!(action, Action::SetTaskStatus { .. }));
     }
 
+    #[test]
+    fn test_add_task_estimate_is_a_string_on_the_wire() {
+        let json = r#"{"type":"task.add","subject":"Write tests","estimate":"4h"}"#;
+        let action: Action = serde_json::from_str(json).expect("deserialization failed");
+
+        let Action::AddTask { estimate: Some(estimate), .. } = &action else {
+            panic!("expected task.add with an estimate");
+        };
+        assert_eq!(estimate.minutes(), Some(240));
+        assert!(serde_json::to_string(&action).unwrap().contains(r#""estimate":"4h""#));
+    }
+
     #[test]
     fn test_edit_task_with_affected_files_roundtrips() {
         use radicle::git::Oid;
@@ -298,6 +442,22 @@ mod tests {
         assert_eq!(action, deserialized);
     }
 
+    #[test]
+    fn test_edit_milestone_clears_due_date() {
+        use radicle::git::Oid;
+
+        let milestone_id = MilestoneId::from(Oid::from_str("0000000000000000000000000000000000000000").unwrap());
+        let clear = Action::EditMilestone { milestone_id, name: None, due: Some(None) };
+        let json = serde_json::to_string(&clear).expect("serialization failed");
+        assert!(json.contains(r#""due":null"#));
+        assert_eq!(serde_json::from_str::<Action>(&json).unwrap(), clear);
+
+        let rename = Action::EditMilestone { milestone_id, name: Some("Beta".to_string()), due: None };
+        let json = serde_json::to_string(&rename).expect("serialization failed");
+        assert!(!json.contains("due"));
+        assert_eq!(serde_json::from_str::<Action>(&json).unwrap(), rename);
+    }
+
     #[test]
     fn test_edit_task_none_affected_files_omitted_in_json() {
         use radicle::git::Oid;
@@ -0,0 +1,280 @@
+//! Batches of plan actions.
+//!
+//! Each [`PlanMut`](crate::PlanMut) method records its action in a change of its own,
+//! ie. a signed commit. A [`Batch`] collects any number of actions instead, to be
+//! recorded together in a single change with [`PlanMut::batch`](crate::PlanMut::batch):
+//!
+//! ```ignore
+//! plan.batch("Add tasks", &signer, |b| {
+//!     b.add_task("Design", None, None, vec![]);
+//!     b.add_task("Implement", None, None, vec![]);
+//!     b.link_issue(issue_id);
+//! })?;
+//! ```
+//!
+//! Actions are applied in the order they are added.
+
+use std::collections::BTreeSet;
+
+use radicle::cob::common::{Label, Title, Uri};
+use radicle::cob::thread::CommentId;
+use radicle::cob::{Embed, EntryId, ObjectId};
+use radicle::git::Oid;
+use radicle::prelude::Did;
+
+use crate::estimate::Estimate;
+use crate::milestone::{DueDate, MilestoneId};
+use crate::state::TaskId;
+use crate::{task_id, Action};
+
+/// Actions to record in a single change.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Batch {
+    actions: Vec<Action>,
+}
+
+impl Batch {
+    /// Get the actions, in the order they were added.
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// Whether no actions were added.
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Get the IDs of the tasks the batch adds once recorded in the given change, in
+    /// the order their actions were added. See [`task_id`].
+    pub fn task_ids(&self, entry: EntryId) -> Vec<TaskId> {
+        let tasks = self
+            .actions
+            .iter()
+            .filter(|a| matches!(a, Action::AddTask { .. }))
+            .count();
+
+        (0..tasks).map(|i| task_id(entry, i)).collect()
+    }
+
+    /// Add an action.
+    pub fn push(&mut self, action: Action) -> &mut Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Edit the plan title.
+    pub fn edit_title(&mut self, title: Title) -> &mut Self {
+        self.push(Action::EditTitle { title })
+    }
+
+    /// Edit the plan description.
+    pub fn edit_description(&mut self, description: impl ToString, embeds: Vec<Embed<Uri>>) -> &mut Self {
+        self.push(Action::EditDescription {
+            description: description.to_string(),
+            embeds,
+        })
+    }
+
+    /// Add a task to the plan.
+    pub fn add_task(
+        &mut self,
+        subject: impl ToString,
+        description: Option<String>,
+        estimate: Option<Estimate>,
+        affected_files: Vec<String>,
+    ) -> &mut Self {
+        self.push(Action::AddTask {
+            subject: subject.to_string(),
+            description,
+            estimate,
+            affected_files,
+        })
+    }
+
+    /// Edit a task.
+    pub fn edit_task(
+        &mut self,
+        task_id: TaskId,
+        subject: Option<String>,
+        description: Option<Option<String>>,
+        estimate: Option<Option<Estimate>>,
+        affected_files: Option<Vec<String>>,
+    ) -> &mut Self {
+        self.push(Action::EditTask {
+            task_id,
+            subject,
+            description,
+            estimate,
+            affected_files,
+        })
+    }
+
+    /// Remove a task from the plan.
+    pub fn remove_task(&mut self, task_id: TaskId) -> &mut Self {
+        self.push(Action::RemoveTask { task_id })
+    }
+
+    /// Reorder the plan's tasks.
+    pub fn reorder_tasks(&mut self, task_ids: Vec<TaskId>) -> &mut Self {
+        self.push(Action::ReorderTasks { task_ids })
+    }
+
+    /// Set the tasks that block a task, replacing any existing blockers.
+    pub fn set_task_blocked_by(&mut self, task_id: TaskId, blocked_by: Vec<TaskId>) -> &mut Self {
+        self.push(Action::SetTaskBlockedBy { task_id, blocked_by })
+    }
+
+    /// Make a task a subtask of another, or a top-level task if `parent` is `None`.
+    pub fn set_task_parent(&mut self, task_id: TaskId, parent: Option<TaskId>) -> &mut Self {
+        self.push(Action::SetTaskParent { task_id, parent })
+    }
+
+    /// Add a task to a milestone, or remove it from its milestone if `milestone_id` is
+    /// `None`.
+    pub fn set_task_milestone(&mut self, task_id: TaskId, milestone_id: Option<MilestoneId>) -> &mut Self {
+        self.push(Action::SetTaskMilestone { task_id, milestone_id })
+    }
+
+    /// Add a milestone to the plan.
+    pub fn add_milestone(&mut self, name: impl ToString, due: Option<DueDate>) -> &mut Self {
+        self.push(Action::AddMilestone { name: name.to_string(), due })
+    }
+
+    /// Edit a milestone.
+    pub fn edit_milestone(
+        &mut self,
+        milestone_id: MilestoneId,
+        name: Option<String>,
+        due: Option<Option<DueDate>>,
+    ) -> &mut Self {
+        self.push(Action::EditMilestone { milestone_id, name, due })
+    }
+
+    /// Remove a milestone. Its tasks are kept, without a milestone.
+    pub fn remove_milestone(&mut self, milestone_id: MilestoneId) -> &mut Self {
+        self.push(Action::RemoveMilestone { milestone_id })
+    }
+
+    /// Reorder the plan's milestones.
+    pub fn reorder_milestones(&mut self, milestone_ids: Vec<MilestoneId>) -> &mut Self {
+        self.push(Action::ReorderMilestones { milestone_ids })
+    }
+
+    /// Link a task to a commit, marking it as done.
+    pub fn link_task_to_commit(&mut self, task_id: TaskId, commit: Oid) -> &mut Self {
+        self.push(Action::LinkTaskToCommit { task_id, commit })
+    }
+
+    /// Link a task to an issue.
+    pub fn link_task_to_issue(&mut self, task_id: TaskId, issue_id: ObjectId) -> &mut Self {
+        self.push(Action::LinkTaskToIssue { task_id, issue_id })
+    }
+
+    /// Link a task to the patch that implements it.
+    pub fn link_task_to_patch(&mut self, task_id: TaskId, patch_id: ObjectId) -> &mut Self {
+        self.push(Action::LinkTaskToPatch { task_id, patch_id })
+    }
+
+    /// Assign users to a task, and unassign others.
+    pub fn assign_task(
+        &mut self,
+        task_id: TaskId,
+        add: impl IntoIterator<Item = Did>,
+        remove: impl IntoIterator<Item = Did>,
+    ) -> &mut Self {
+        self.push(Action::AssignTask {
+            task_id,
+            add: add.into_iter().collect::<BTreeSet<_>>(),
+            remove: remove.into_iter().collect::<BTreeSet<_>>(),
+        })
+    }
+
+    /// Link an issue to the plan.
+    pub fn link_issue(&mut self, issue_id: ObjectId) -> &mut Self {
+        self.push(Action::LinkIssue { issue_id })
+    }
+
+    /// Unlink an issue from the plan.
+    pub fn unlink_issue(&mut self, issue_id: ObjectId) -> &mut Self {
+        self.push(Action::UnlinkIssue { issue_id })
+    }
+
+    /// Link a patch to the plan.
+    pub fn link_patch(&mut self, patch_id: ObjectId) -> &mut Self {
+        self.push(Action::LinkPatch { patch_id })
+    }
+
+    /// Unlink a patch from the plan.
+    pub fn unlink_patch(&mut self, patch_id: ObjectId) -> &mut Self {
+        self.push(Action::UnlinkPatch { patch_id })
+    }
+
+    /// Add a critical file.
+    pub fn add_critical_file(&mut self, path: impl ToString) -> &mut Self {
+        self.push(Action::AddCriticalFile { path: path.to_string() })
+    }
+
+    /// Remove a critical file.
+    pub fn remove_critical_file(&mut self, path: impl ToString) -> &mut Self {
+        self.push(Action::RemoveCriticalFile { path: path.to_string() })
+    }
+
+    /// Add a comment to the plan.
+    pub fn comment(&mut self, body: impl ToString, reply_to: Option<CommentId>, embeds: Vec<Embed<Uri>>) -> &mut Self {
+        self.push(Action::Comment {
+            body: body.to_string(),
+            reply_to,
+            embeds,
+        })
+    }
+
+    /// Label the plan.
+    pub fn label(&mut self, labels: impl IntoIterator<Item = Label>) -> &mut Self {
+        self.push(Action::Label {
+            labels: labels.into_iter().collect::<BTreeSet<_>>(),
+        })
+    }
+
+    /// Assign DIDs to the plan.
+    pub fn assign(&mut self, assignees: impl IntoIterator<Item = Did>) -> &mut Self {
+        self.push(Action::Assign {
+            assignees: assignees.into_iter().collect::<BTreeSet<_>>(),
+        })
+    }
+}
+
+impl IntoIterator for Batch {
+    type Item = Action;
+    type IntoIter = std::vec::IntoIter<Action>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.actions.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_order() {
+        let mut batch = Batch::default();
+        assert!(batch.is_empty());
+
+        batch
+            .add_task("Design", None, None, vec![])
+            .add_critical_file("src/lib.rs")
+            .add_task("Implement", None, None, vec![]);
+
+        let subjects = batch
+            .actions()
+            .iter()
+            .filter_map(|a| match a {
+                Action::AddTask { subject, .. } => Some(subject.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["Design", "Implement"]);
+        assert_eq!(batch.into_iter().count(), 3);
+    }
+}
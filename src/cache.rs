@@ -0,0 +1,384 @@
+//! SQLite cache of plans.
+//!
+//! Like radicle's issue and patch caches, the plan cache stores each plan's evaluated
+//! state as JSON, along with the columns needed to answer list queries, so that listing
+//! plans doesn't require replaying every plan's change history. The cache is kept up to
+//! date by writing through on every change made via [`PlansMut`](crate::PlansMut), and
+//! can be rebuilt from the repository at any time.
+//!
+//! Changes fetched from other peers don't go through the cache. Each plan is therefore
+//! cached with the heads of its refs, so that [`PlansMut::refresh`](crate::PlansMut::refresh)
+//! can find the plans that changed since, and re-evaluate only those.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use sqlite as sql;
+use thiserror::Error;
+
+use radicle::cob::common::Label;
+use radicle::prelude::RepoId;
+
+use crate::{Plan, PlanCounts, PlanId, PlanStatus};
+
+/// File name of the plan cache database, in the profile's `cobs` directory.
+pub const CACHE_FILE: &str = "plans.db";
+
+/// Schema migrations, applied in order. The schema version is the number applied.
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE IF NOT EXISTS plans (
+        repo   TEXT NOT NULL,
+        id     TEXT NOT NULL,
+        status TEXT NOT NULL,
+        plan   TEXT NOT NULL,
+        PRIMARY KEY (repo, id)
+    );
+    CREATE TABLE IF NOT EXISTS plan_labels (
+        repo  TEXT NOT NULL,
+        id    TEXT NOT NULL,
+        label TEXT NOT NULL,
+        PRIMARY KEY (repo, id, label)
+    );
+    ",
+    // The heads of a plan's refs when it was cached, or `NULL` if unknown.
+    "
+    ALTER TABLE plans ADD COLUMN heads TEXT;
+    ",
+];
+
+/// Error reading or writing the plan cache.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// Database error.
+    #[error("sql: {0}")]
+    Sql(#[from] sql::Error),
+    /// Error (de)serializing a cached plan.
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A cached plan ID could not be parsed.
+    #[error("invalid plan id in cache: {0}")]
+    InvalidId(String),
+    /// A cached plan status could not be parsed.
+    #[error("invalid plan status in cache: {0}")]
+    InvalidStatus(String),
+}
+
+/// Write access to a plan cache.
+pub trait Update {
+    /// Insert or update a plan.
+    fn update(&mut self, rid: &RepoId, id: &PlanId, plan: &Plan) -> Result<(), Error>;
+
+    /// Remove a plan.
+    fn remove(&mut self, rid: &RepoId, id: &PlanId) -> Result<(), Error>;
+}
+
+/// A cache that discards all writes, used when plans are mutated without a cache.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoCache;
+
+impl Update for NoCache {
+    fn update(&mut self, _rid: &RepoId, _id: &PlanId, _plan: &Plan) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn remove(&mut self, _rid: &RepoId, _id: &PlanId) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<T: Update> Update for &mut T {
+    fn update(&mut self, rid: &RepoId, id: &PlanId, plan: &Plan) -> Result<(), Error> {
+        (**self).update(rid, id, plan)
+    }
+
+    fn remove(&mut self, rid: &RepoId, id: &PlanId) -> Result<(), Error> {
+        (**self).remove(rid, id)
+    }
+}
+
+/// A SQLite-backed plan cache.
+pub struct PlanCache {
+    db: sql::Connection,
+}
+
+impl std::fmt::Debug for PlanCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PlanCache").finish_non_exhaustive()
+    }
+}
+
+impl PlanCache {
+    /// Open a plan cache at the given path, creating it if necessary.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let db = sql::Connection::open(path)?;
+        Self::init(db)
+    }
+
+    /// Create an in-memory plan cache.
+    pub fn memory() -> Result<Self, Error> {
+        let db = sql::Connection::open(":memory:")?;
+        Self::init(db)
+    }
+
+    fn init(db: sql::Connection) -> Result<Self, Error> {
+        let mut stmt = db.prepare("PRAGMA user_version")?;
+        let version = match stmt.next()? {
+            sql::State::Row => stmt.read::<i64, _>(0)?,
+            sql::State::Done => 0,
+        };
+        drop(stmt);
+
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            db.execute(migration)?;
+            db.execute(format!("PRAGMA user_version = {}", i + 1))?;
+        }
+        Ok(Self { db })
+    }
+
+    /// Get a cached plan.
+    pub fn get(&self, rid: &RepoId, id: &PlanId) -> Result<Option<Plan>, Error> {
+        let mut stmt = self
+            .db
+            .prepare("SELECT id, plan FROM plans WHERE repo = ?1 AND id = ?2")?;
+        stmt.bind((1, rid.to_string().as_str()))?;
+        stmt.bind((2, id.to_string().as_str()))?;
+
+        Ok(Self::read_plans(stmt)?.into_iter().next().map(|(_, plan)| plan))
+    }
+
+    /// List all cached plans of a repository.
+    pub fn list(&self, rid: &RepoId) -> Result<Vec<(PlanId, Plan)>, Error> {
+        let mut stmt = self
+            .db
+            .prepare("SELECT id, plan FROM plans WHERE repo = ?1 ORDER BY id")?;
+        stmt.bind((1, rid.to_string().as_str()))?;
+
+        Self::read_plans(stmt)
+    }
+
+    /// List the cached plans of a repository that have the given status.
+    pub fn list_by_status(&self, rid: &RepoId, status: &PlanStatus) -> Result<Vec<(PlanId, Plan)>, Error> {
+        let mut stmt = self
+            .db
+            .prepare("SELECT id, plan FROM plans WHERE repo = ?1 AND status = ?2 ORDER BY id")?;
+        stmt.bind((1, rid.to_string().as_str()))?;
+        stmt.bind((2, status.to_string().as_str()))?;
+
+        Self::read_plans(stmt)
+    }
+
+    /// List the cached plans of a repository that have the given label.
+    pub fn list_by_label(&self, rid: &RepoId, label: &Label) -> Result<Vec<(PlanId, Plan)>, Error> {
+        let mut stmt = self.db.prepare(
+            "SELECT p.id, p.plan FROM plans p
+             JOIN plan_labels l ON l.repo = p.repo AND l.id = p.id
+             WHERE p.repo = ?1 AND l.label = ?2
+             ORDER BY p.id",
+        )?;
+        stmt.bind((1, rid.to_string().as_str()))?;
+        stmt.bind((2, label.name()))?;
+
+        Self::read_plans(stmt)
+    }
+
+    /// Count the cached plans of a repository by status.
+    pub fn counts(&self, rid: &RepoId) -> Result<PlanCounts, Error> {
+        let mut stmt = self
+            .db
+            .prepare("SELECT status, COUNT(*) FROM plans WHERE repo = ?1 GROUP BY status")?;
+        stmt.bind((1, rid.to_string().as_str()))?;
+
+        let mut counts = PlanCounts::default();
+        while let sql::State::Row = stmt.next()? {
+            let status = stmt.read::<String, _>(0)?;
+            let count = stmt.read::<i64, _>(1)? as usize;
+
+            match PlanStatus::from_str(&status).map_err(|_| Error::InvalidStatus(status))? {
+                PlanStatus::Draft => counts.draft += count,
+                PlanStatus::Approved => counts.approved += count,
+                PlanStatus::InProgress => counts.in_progress += count,
+                PlanStatus::Completed => counts.completed += count,
+                PlanStatus::Archived => counts.archived += count,
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Get the ref heads each cached plan of a repository was evaluated from, if known.
+    /// See [`PlanCache::set_heads`].
+    pub fn heads(&self, rid: &RepoId) -> Result<BTreeMap<PlanId, Option<String>>, Error> {
+        let mut stmt = self.db.prepare("SELECT id, heads FROM plans WHERE repo = ?1")?;
+        stmt.bind((1, rid.to_string().as_str()))?;
+
+        let mut heads = BTreeMap::new();
+        while let sql::State::Row = stmt.next()? {
+            let id = stmt.read::<String, _>(0)?;
+            let id = PlanId::from_str(&id).map_err(|_| Error::InvalidId(id))?;
+
+            heads.insert(id, stmt.read::<Option<String>, _>(1)?);
+        }
+        Ok(heads)
+    }
+
+    /// Record the ref heads a cached plan was evaluated from. Updating the plan resets
+    /// them, as the write may not have been made from the current heads.
+    pub fn set_heads(&mut self, rid: &RepoId, id: &PlanId, heads: &str) -> Result<(), Error> {
+        let mut stmt = self
+            .db
+            .prepare("UPDATE plans SET heads = ?3 WHERE repo = ?1 AND id = ?2")?;
+        stmt.bind((1, rid.to_string().as_str()))?;
+        stmt.bind((2, id.to_string().as_str()))?;
+        stmt.bind((3, heads))?;
+        while stmt.next()? != sql::State::Done {}
+
+        Ok(())
+    }
+
+    /// Remove all cached plans of a repository.
+    pub fn remove_all(&mut self, rid: &RepoId) -> Result<(), Error> {
+        for table in ["plans", "plan_labels"] {
+            let mut stmt = self.db.prepare(format!("DELETE FROM {table} WHERE repo = ?1"))?;
+            stmt.bind((1, rid.to_string().as_str()))?;
+            while stmt.next()? != sql::State::Done {}
+        }
+        Ok(())
+    }
+
+    fn read_plans(mut stmt: sql::Statement<'_>) -> Result<Vec<(PlanId, Plan)>, Error> {
+        let mut plans = Vec::new();
+
+        while let sql::State::Row = stmt.next()? {
+            let id = stmt.read::<String, _>(0)?;
+            let id = PlanId::from_str(&id).map_err(|_| Error::InvalidId(id))?;
+            let plan = serde_json::from_str(&stmt.read::<String, _>(1)?)?;
+
+            plans.push((id, plan));
+        }
+        Ok(plans)
+    }
+}
+
+impl Update for PlanCache {
+    fn update(&mut self, rid: &RepoId, id: &PlanId, plan: &Plan) -> Result<(), Error> {
+        let rid = rid.to_string();
+        let id = id.to_string();
+        let json = serde_json::to_string(plan)?;
+
+        self.db.execute("BEGIN")?;
+        let result = (|| {
+            let mut stmt = self.db.prepare(
+                "INSERT INTO plans (repo, id, status, plan) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (repo, id) DO UPDATE SET status = ?3, plan = ?4, heads = NULL",
+            )?;
+            stmt.bind((1, rid.as_str()))?;
+            stmt.bind((2, id.as_str()))?;
+            stmt.bind((3, plan.status().to_string().as_str()))?;
+            stmt.bind((4, json.as_str()))?;
+            while stmt.next()? != sql::State::Done {}
+
+            let mut stmt = self.db.prepare("DELETE FROM plan_labels WHERE repo = ?1 AND id = ?2")?;
+            stmt.bind((1, rid.as_str()))?;
+            stmt.bind((2, id.as_str()))?;
+            while stmt.next()? != sql::State::Done {}
+
+            for label in plan.labels() {
+                let mut stmt = self
+                    .db
+                    .prepare("INSERT INTO plan_labels (repo, id, label) VALUES (?1, ?2, ?3)")?;
+                stmt.bind((1, rid.as_str()))?;
+                stmt.bind((2, id.as_str()))?;
+                stmt.bind((3, label.name()))?;
+                while stmt.next()? != sql::State::Done {}
+            }
+            Ok::<_, Error>(())
+        })();
+
+        match result {
+            Ok(()) => self.db.execute("COMMIT")?,
+            Err(_) => self.db.execute("ROLLBACK")?,
+        }
+        result
+    }
+
+    fn remove(&mut self, rid: &RepoId, id: &PlanId) -> Result<(), Error> {
+        for table in ["plans", "plan_labels"] {
+            let mut stmt = self
+                .db
+                .prepare(format!("DELETE FROM {table} WHERE repo = ?1 AND id = ?2"))?;
+            stmt.bind((1, rid.to_string().as_str()))?;
+            stmt.bind((2, id.to_string().as_str()))?;
+            while stmt.next()? != sql::State::Done {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use radicle::git::Oid;
+
+    use crate::state::test;
+
+    fn rid() -> RepoId {
+        RepoId::from_str("rad:z3gqcJUoA1n9HaHKufZs5FCSGazv5").unwrap()
+    }
+
+    fn plan(n: u8, status: PlanStatus, labels: &[&str]) -> (PlanId, Plan) {
+        let id = Oid::from_str(&format!("{n:040x}")).unwrap();
+        let mut plan = test::plan(id);
+        plan.title = format!("Plan {n}");
+        plan.status = status;
+        plan.labels = labels.iter().map(|l| Label::from_str(l).unwrap()).collect();
+
+        (PlanId::from(id), plan)
+    }
+
+    #[test]
+    fn test_write_through_and_queries() {
+        let mut cache = PlanCache::memory().unwrap();
+        let (a, plan_a) = plan(1, PlanStatus::Draft, &["ux"]);
+        let (b, plan_b) = plan(2, PlanStatus::InProgress, &["ux", "backend"]);
+
+        cache.update(&rid(), &a, &plan_a).unwrap();
+        cache.update(&rid(), &b, &plan_b).unwrap();
+        assert_eq!(cache.get(&rid(), &a).unwrap().as_ref().map(Plan::title), Some("Plan 1"));
+
+        let counts = cache.counts(&rid()).unwrap();
+        assert_eq!((counts.draft, counts.in_progress, counts.total()), (1, 1, 2));
+
+        let in_progress = cache.list_by_status(&rid(), &PlanStatus::InProgress).unwrap();
+        assert_eq!(in_progress.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![b]);
+
+        let backend = Label::from_str("backend").unwrap();
+        let labelled = cache.list_by_label(&rid(), &backend).unwrap();
+        assert_eq!(labelled.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![b]);
+
+        // Updating a plan replaces its labels.
+        let (_, plan_b) = plan(2, PlanStatus::Completed, &["ux"]);
+        cache.update(&rid(), &b, &plan_b).unwrap();
+        assert!(cache.list_by_label(&rid(), &backend).unwrap().is_empty());
+        assert_eq!(cache.counts(&rid()).unwrap().completed, 1);
+
+        cache.remove(&rid(), &a).unwrap();
+        assert_eq!(cache.list(&rid()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_heads() {
+        let mut cache = PlanCache::memory().unwrap();
+        let (a, plan_a) = plan(1, PlanStatus::Draft, &[]);
+
+        cache.update(&rid(), &a, &plan_a).unwrap();
+        assert_eq!(cache.heads(&rid()).unwrap().get(&a), Some(&None));
+
+        cache.set_heads(&rid(), &a, "abc").unwrap();
+        assert_eq!(cache.heads(&rid()).unwrap().get(&a), Some(&Some("abc".to_string())));
+
+        // Writing the plan through forgets the heads it was evaluated from.
+        cache.update(&rid(), &a, &plan_a).unwrap();
+        assert_eq!(cache.heads(&rid()).unwrap().get(&a), Some(&None));
+    }
+}
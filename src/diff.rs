@@ -0,0 +1,411 @@
+//! Differences between two revisions of a plan.
+//!
+//! A [`PlanDiff`] compares two evaluated plans, typically the same plan at two points
+//! in its history (see [`Plans::get_at`](crate::Plans::get_at)), and describes what
+//! changed: title and description, status, tasks, milestones, and linked objects.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use radicle::cob::common::Label;
+use radicle::cob::ObjectId;
+use radicle::git::Oid;
+use radicle::prelude::Did;
+
+use crate::estimate::Estimate;
+use crate::milestone::{DueDate, Milestone, MilestoneId};
+use crate::state::{Plan, PlanStatus, Task, TaskId};
+
+/// A value that changed between two revisions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change<T> {
+    /// Value in the old revision.
+    pub old: T,
+    /// Value in the new revision.
+    pub new: T,
+}
+
+impl<T: PartialEq + Clone> Change<T> {
+    /// Compare two values, returning a change if they differ.
+    pub fn of(old: &T, new: &T) -> Option<Self> {
+        (old != new).then(|| Self {
+            old: old.clone(),
+            new: new.clone(),
+        })
+    }
+}
+
+/// Elements added to and removed from a set between two revisions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SetChange<T> {
+    /// Elements only in the new revision.
+    pub added: Vec<T>,
+    /// Elements only in the old revision.
+    pub removed: Vec<T>,
+}
+
+impl<T: Ord + Clone> SetChange<T> {
+    /// Compare two sets.
+    pub fn of<'a>(old: impl IntoIterator<Item = &'a T>, new: impl IntoIterator<Item = &'a T>) -> Self
+    where
+        T: 'a,
+    {
+        let old = old.into_iter().collect::<BTreeSet<_>>();
+        let new = new.into_iter().collect::<BTreeSet<_>>();
+
+        Self {
+            added: new.difference(&old).map(|t| (*t).clone()).collect(),
+            removed: old.difference(&new).map(|t| (*t).clone()).collect(),
+        }
+    }
+
+    /// Whether the sets are the same.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A line of a text diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type", content = "line")]
+pub enum DiffLine {
+    /// Line present in both revisions.
+    Unchanged(String),
+    /// Line only in the new revision.
+    Added(String),
+    /// Line only in the old revision.
+    Removed(String),
+}
+
+/// Compute a line diff between two texts, based on their longest common subsequence.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Unchanged(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] > lcs[i + 1][j]) {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        } else {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        }
+    }
+    lines
+}
+
+/// Changes to a task present in both revisions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDiff {
+    /// Task identifier.
+    pub id: TaskId,
+    /// Task subject in the new revision.
+    pub subject: String,
+    /// Subject change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject_change: Option<Change<String>>,
+    /// Description change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<Change<Option<String>>>,
+    /// Estimate change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Change<Option<Estimate>>>,
+    /// Affected files change.
+    #[serde(skip_serializing_if = "SetChange::is_empty")]
+    pub affected_files: SetChange<String>,
+    /// Blockers change.
+    #[serde(skip_serializing_if = "SetChange::is_empty")]
+    pub blocked_by: SetChange<TaskId>,
+    /// Parent change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Change<Option<TaskId>>>,
+    /// Milestone change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milestone: Option<Change<Option<MilestoneId>>>,
+    /// Assignees change.
+    #[serde(skip_serializing_if = "SetChange::is_empty")]
+    pub assignees: SetChange<Did>,
+    /// Linked issue change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linked_issue: Option<Change<Option<ObjectId>>>,
+    /// Linked commit change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linked_commit: Option<Change<Option<Oid>>>,
+    /// Linked patch change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linked_patch: Option<Change<Option<ObjectId>>>,
+}
+
+impl TaskDiff {
+    /// Compare two revisions of a task.
+    pub fn new(old: &Task, new: &Task) -> Self {
+        Self {
+            id: new.id,
+            subject: new.subject.clone(),
+            subject_change: Change::of(&old.subject, &new.subject),
+            description: Change::of(&old.description, &new.description),
+            estimate: Change::of(&old.estimate, &new.estimate),
+            affected_files: SetChange::of(&old.affected_files, &new.affected_files),
+            blocked_by: SetChange::of(&old.blocked_by, &new.blocked_by),
+            parent: Change::of(&old.parent, &new.parent),
+            milestone: Change::of(&old.milestone, &new.milestone),
+            assignees: SetChange::of(&old.assignees, &new.assignees),
+            linked_issue: Change::of(&old.linked_issue, &new.linked_issue),
+            linked_commit: Change::of(&old.linked_commit, &new.linked_commit),
+            linked_patch: Change::of(&old.linked_patch, &new.linked_patch),
+        }
+    }
+
+    /// Whether the task is unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.subject_change.is_none()
+            && self.description.is_none()
+            && self.estimate.is_none()
+            && self.affected_files.is_empty()
+            && self.blocked_by.is_empty()
+            && self.parent.is_none()
+            && self.milestone.is_none()
+            && self.assignees.is_empty()
+            && self.linked_issue.is_none()
+            && self.linked_commit.is_none()
+            && self.linked_patch.is_none()
+    }
+}
+
+/// Changes to a milestone present in both revisions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MilestoneDiff {
+    /// Milestone identifier.
+    pub id: MilestoneId,
+    /// Milestone name in the new revision.
+    pub name: String,
+    /// Name change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_change: Option<Change<String>>,
+    /// Due date change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<Change<Option<DueDate>>>,
+}
+
+impl MilestoneDiff {
+    /// Compare two revisions of a milestone.
+    pub fn new(old: &Milestone, new: &Milestone) -> Self {
+        Self {
+            id: new.id,
+            name: new.name.clone(),
+            name_change: Change::of(&old.name, &new.name),
+            due: Change::of(&old.due, &new.due),
+        }
+    }
+
+    /// Whether the milestone is unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.name_change.is_none() && self.due.is_none()
+    }
+}
+
+/// Differences between two revisions of a plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanDiff {
+    /// Title change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<Change<String>>,
+    /// Line diff of the description, if it changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<Vec<DiffLine>>,
+    /// Status transition.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Change<PlanStatus>>,
+    /// Tasks only in the new revision.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tasks_added: Vec<Task>,
+    /// Tasks only in the old revision.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tasks_removed: Vec<Task>,
+    /// Tasks present in both revisions that changed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tasks_edited: Vec<TaskDiff>,
+    /// Whether tasks present in both revisions changed order.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub tasks_reordered: bool,
+    /// Milestones only in the new revision.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub milestones_added: Vec<Milestone>,
+    /// Milestones only in the old revision.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub milestones_removed: Vec<Milestone>,
+    /// Milestones present in both revisions that changed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub milestones_edited: Vec<MilestoneDiff>,
+    /// Whether milestones present in both revisions changed order.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub milestones_reordered: bool,
+    /// Linked issues.
+    #[serde(skip_serializing_if = "SetChange::is_empty")]
+    pub issues: SetChange<ObjectId>,
+    /// Linked patches.
+    #[serde(skip_serializing_if = "SetChange::is_empty")]
+    pub patches: SetChange<ObjectId>,
+    /// Critical files.
+    #[serde(skip_serializing_if = "SetChange::is_empty")]
+    pub critical_files: SetChange<String>,
+    /// Labels.
+    #[serde(skip_serializing_if = "SetChange::is_empty")]
+    pub labels: SetChange<Label>,
+    /// Assignees.
+    #[serde(skip_serializing_if = "SetChange::is_empty")]
+    pub assignees: SetChange<Did>,
+}
+
+impl PlanDiff {
+    /// Compare two revisions of a plan.
+    pub fn new(old: &Plan, new: &Plan) -> Self {
+        let description = (old.description() != new.description())
+            .then(|| diff_lines(old.description(), new.description()));
+
+        let tasks_added = new
+            .tasks()
+            .iter()
+            .filter(|t| old.task(&t.id).is_none())
+            .cloned()
+            .collect();
+        let tasks_removed = old
+            .tasks()
+            .iter()
+            .filter(|t| new.task(&t.id).is_none())
+            .cloned()
+            .collect();
+        let tasks_edited = new
+            .tasks()
+            .iter()
+            .filter_map(|t| old.task(&t.id).map(|o| TaskDiff::new(o, t)))
+            .filter(|d| !d.is_empty())
+            .collect();
+
+        let milestones_added = new
+            .milestones()
+            .iter()
+            .filter(|m| old.milestone(&m.id).is_none())
+            .cloned()
+            .collect();
+        let milestones_removed = old
+            .milestones()
+            .iter()
+            .filter(|m| new.milestone(&m.id).is_none())
+            .cloned()
+            .collect();
+        let milestones_edited = new
+            .milestones()
+            .iter()
+            .filter_map(|m| old.milestone(&m.id).map(|o| MilestoneDiff::new(o, m)))
+            .filter(|d| !d.is_empty())
+            .collect();
+
+        let common = |plan: &Plan, other: &Plan| {
+            plan.tasks()
+                .iter()
+                .filter(|t| other.task(&t.id).is_some())
+                .map(|t| t.id)
+                .collect::<Vec<_>>()
+        };
+        let common_milestones = |plan: &Plan, other: &Plan| {
+            plan.milestones()
+                .iter()
+                .filter(|m| other.milestone(&m.id).is_some())
+                .map(|m| m.id)
+                .collect::<Vec<_>>()
+        };
+
+        Self {
+            title: Change::of(&old.title().to_string(), &new.title().to_string()),
+            description,
+            status: Change::of(old.status(), new.status()),
+            tasks_added,
+            tasks_removed,
+            tasks_edited,
+            tasks_reordered: common(old, new) != common(new, old),
+            milestones_added,
+            milestones_removed,
+            milestones_edited,
+            milestones_reordered: common_milestones(old, new) != common_milestones(new, old),
+            issues: SetChange::of(old.related_issues(), new.related_issues()),
+            patches: SetChange::of(old.related_patches(), new.related_patches()),
+            critical_files: SetChange::of(old.critical_files(), new.critical_files()),
+            labels: SetChange::of(old.labels(), new.labels()),
+            assignees: SetChange::of(old.assignees(), new.assignees()),
+        }
+    }
+
+    /// Whether the revisions are the same.
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.status.is_none()
+            && self.tasks_added.is_empty()
+            && self.tasks_removed.is_empty()
+            && self.tasks_edited.is_empty()
+            && !self.tasks_reordered
+            && self.milestones_added.is_empty()
+            && self.milestones_removed.is_empty()
+            && self.milestones_edited.is_empty()
+            && !self.milestones_reordered
+            && self.issues.is_empty()
+            && self.patches.is_empty()
+            && self.critical_files.is_empty()
+            && self.labels.is_empty()
+            && self.assignees.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let old = "intro\nfirst\nsecond\noutro";
+        let new = "intro\nsecond\nthird\noutro";
+
+        assert_eq!(
+            diff_lines(old, new),
+            vec![
+                DiffLine::Unchanged("intro".to_string()),
+                DiffLine::Removed("first".to_string()),
+                DiffLine::Unchanged("second".to_string()),
+                DiffLine::Added("third".to_string()),
+                DiffLine::Unchanged("outro".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_change() {
+        let change = SetChange::of(&[1, 2, 3], &[2, 3, 4]);
+
+        assert_eq!(change.added, vec![4]);
+        assert_eq!(change.removed, vec![1]);
+        assert!(SetChange::of(&[1], &[1]).is_empty());
+    }
+}
@@ -0,0 +1,1261 @@
+//! Markdown plan documents.
+//!
+//! A plan document is a plan written as a Markdown checklist, as produced by
+//! `rad-plan export`:
+//!
+//! ```markdown
+//! # Title
+//!
+//! **Status:** draft
+//!
+//! ## Description
+//!
+//! Free-form description.
+//!
+//! ## Tasks
+//!
+//! - [ ] Subject _(2h)_ <!-- task: 9f8e7d6 -->
+//!   - Task description
+//! - [x] Done subject <!-- task: 5c4b3a2 --> <!-- commit: 1a2b3c... -->
+//! - [ ] Blocked subject <!-- blocked-by: 1, 2 -->
+//!   - [ ] Subtask
+//!
+//! ### Milestone _(due 2025-06-30)_ <!-- milestone: 3d2c1b0 -->
+//!
+//! - [ ] Subject of a task in the milestone
+//!
+//! ## Linked Issues
+//!
+//! - <issue-id>
+//!
+//! ## Linked Patches
+//!
+//! - <patch-id>
+//! ```
+//!
+//! Blockers are referred to by their 1-based position in the task list. A checklist
+//! item nested under a task is a subtask of it; other nested lines describe the task.
+//! Tasks listed under a `### Milestone` heading belong to that milestone, and tasks
+//! that belong to no milestone are listed first.
+//!
+//! `**Key:** value` lines before the first section are kept as metadata. Text before
+//! the first section that isn't metadata is taken as part of the description. Other
+//! sections are ignored.
+//!
+//! A document can also be applied to an existing plan: see [`PlanDocument::sync`].
+//! Tasks are matched to the plan's tasks by their embedded task ID, or failing that,
+//! by subject, and milestones likewise by their embedded ID or name.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use radicle::cob::{self, EntryId, Title};
+use radicle::git::Oid;
+use radicle::prelude::ReadRepository;
+
+use crate::actions::Action;
+use crate::estimate::Estimate;
+use crate::milestone::{DueDate, MilestoneId};
+use crate::state::{tree_order, Plan, Task, TaskId};
+use crate::{milestone_id, task_id};
+
+/// Length of the short task and milestone IDs embedded in documents.
+const SHORT_ID_LEN: usize = 7;
+
+/// Error parsing a plan document.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DocumentError {
+    /// The document has no `# Title` heading.
+    #[error("document has no title: expected a `# Title` heading")]
+    MissingTitle,
+    /// A task has an empty subject.
+    #[error("line {0}: task has no subject")]
+    EmptyTask(usize),
+    /// The title is not a valid plan title.
+    #[error("invalid title: {0}")]
+    InvalidTitle(String),
+    /// A task's commit is not a valid commit ID.
+    #[error("line {0}: invalid commit '{1}'")]
+    InvalidCommit(usize, String),
+    /// A task's blocker is not the position of another task in the list.
+    #[error("line {0}: invalid blocker '{1}': expected the number of another task in the list")]
+    InvalidBlocker(usize, String),
+    /// A milestone heading has an empty name.
+    #[error("line {0}: milestone has no name")]
+    EmptyMilestone(usize),
+    /// A milestone's due date is not a valid date.
+    #[error("line {0}: invalid due date '{1}': expected YYYY-MM-DD")]
+    InvalidDueDate(usize, String),
+}
+
+/// A task in a plan document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentTask {
+    /// Task subject.
+    pub subject: String,
+    /// Task description, from the bullets nested under the task.
+    pub description: Option<String>,
+    /// Task estimate, from a trailing `_(...)_`.
+    pub estimate: Option<Estimate>,
+    /// Whether the task is checked.
+    pub done: bool,
+    /// ID, or ID prefix, of the plan task this is, from a trailing `<!-- task: <id> -->`.
+    pub id: Option<String>,
+    /// Tasks blocking this one, by 1-based position in the document, from a trailing
+    /// `<!-- blocked-by: <n>, ... -->`.
+    pub blocked_by: Vec<usize>,
+    /// Commit the task was done in, from a trailing `<!-- commit: <oid> -->`.
+    pub commit: Option<Oid>,
+    /// Task this is a subtask of, by 1-based position in the document, from the
+    /// checklist item it is nested under. Parents always precede their subtasks.
+    pub parent: Option<usize>,
+    /// Milestone the task belongs to, by 1-based position in the document's milestones,
+    /// from the `### Milestone` heading it is listed under.
+    pub milestone: Option<usize>,
+}
+
+/// A milestone in a plan document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentMilestone {
+    /// Milestone name.
+    pub name: String,
+    /// Due date, from a trailing `_(due YYYY-MM-DD)_`.
+    pub due: Option<DueDate>,
+    /// ID, or ID prefix, of the plan milestone this is, from a trailing
+    /// `<!-- milestone: <id> -->`.
+    pub id: Option<String>,
+}
+
+/// A plan written as a Markdown checklist.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlanDocument {
+    /// Plan title.
+    pub title: String,
+    /// Plan description.
+    pub description: String,
+    /// `**Key:** value` lines, in order, eg. the plan status.
+    pub metadata: Vec<(String, String)>,
+    /// Tasks, in order.
+    pub tasks: Vec<DocumentTask>,
+    /// Milestones, in order.
+    pub milestones: Vec<DocumentMilestone>,
+    /// Linked issue IDs, as written. These may be short IDs.
+    pub issues: Vec<String>,
+    /// Linked patch IDs, as written. These may be short IDs.
+    pub patches: Vec<String>,
+}
+
+/// Section of a document being parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Preamble,
+    Description,
+    Tasks,
+    Issues,
+    Patches,
+    Other,
+}
+
+impl PlanDocument {
+    /// Create a document from a plan. Metadata is left for the caller to fill in.
+    ///
+    /// Tasks done by merging their linked patch aren't checked; see
+    /// [`PlanDocument::from_plan_in`].
+    pub fn from_plan(plan: &Plan) -> Self {
+        Self::with_done(plan, |id| plan.is_task_done(id))
+    }
+
+    /// Create a document from a plan, checking tasks whose linked patch has been merged.
+    /// See [`Plan::is_task_done_in`].
+    pub fn from_plan_in<R>(plan: &Plan, repo: &R) -> Result<Self, crate::Error>
+    where
+        R: ReadRepository + cob::Store,
+    {
+        let mut done = BTreeSet::new();
+        for task in plan.tasks() {
+            if plan.is_task_done_in(&task.id, repo)? {
+                done.insert(task.id);
+            }
+        }
+        Ok(Self::with_done(plan, |id| done.contains(id)))
+    }
+
+    fn with_done(plan: &Plan, is_done: impl Fn(&TaskId) -> bool) -> Self {
+        // Tasks are grouped by milestone. Within a milestone, subtasks are nested under
+        // their parent, so tasks are listed as a tree; a subtask in another milestone
+        // than its parent is listed at the top level of its own.
+        let groups = std::iter::once(None).chain(plan.milestones().iter().map(|m| Some(&m.id)));
+        let listed = groups
+            .enumerate()
+            .flat_map(|(g, id)| {
+                let g = (g > 0).then_some(g);
+                plan.milestone_task_tree(id).into_iter().map(move |(_, t)| (t, g))
+            })
+            .collect::<Vec<_>>();
+        let position = |id: &TaskId| listed.iter().position(|(t, _)| t.id == *id).map(|i| i + 1);
+        let tasks = listed
+            .iter()
+            .map(|(t, g)| DocumentTask {
+                subject: t.subject.clone(),
+                description: t.description.clone().filter(|d| !d.is_empty()),
+                estimate: t.estimate.clone(),
+                done: is_done(&t.id),
+                id: Some(t.id.to_string()[..SHORT_ID_LEN].to_string()),
+                blocked_by: t.blocked_by.iter().filter_map(position).collect(),
+                commit: t.linked_commit,
+                parent: t
+                    .parent
+                    .filter(|p| plan.task(p).is_some_and(|p| p.milestone == t.milestone))
+                    .as_ref()
+                    .and_then(position),
+                milestone: *g,
+            })
+            .collect();
+        let milestones = plan
+            .milestones()
+            .iter()
+            .map(|m| DocumentMilestone {
+                name: m.name.clone(),
+                due: m.due,
+                id: Some(m.id.to_string()[..SHORT_ID_LEN].to_string()),
+            })
+            .collect();
+
+        Self {
+            title: plan.title().to_string(),
+            description: plan.description().to_string(),
+            metadata: Vec::new(),
+            tasks,
+            milestones,
+            issues: plan.related_issues().map(|i| i.to_string()).collect(),
+            patches: plan.related_patches().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    /// Parse a Markdown plan document.
+    pub fn parse(s: &str) -> Result<Self, DocumentError> {
+        let mut doc = Self::default();
+        let mut title = None;
+        let mut section = Section::Preamble;
+        let mut preamble = Vec::new();
+        let mut description = Vec::new();
+        // Line number of each task, for reporting errors.
+        let mut lines = Vec::new();
+        // Indentation and index of the tasks enclosing the current line.
+        let mut nesting: Vec<(usize, usize)> = Vec::new();
+        // Milestone of the tasks section being parsed.
+        let mut milestone = None;
+
+        for (n, line) in s.lines().enumerate() {
+            let n = n + 1;
+            let trimmed = line.trim();
+
+            if let Some(heading) = trimmed.strip_prefix("# ") {
+                if title.is_none() {
+                    title = Some(heading.trim().to_string());
+                    continue;
+                }
+            }
+            if let Some(heading) = trimmed.strip_prefix("## ") {
+                section = Section::from_heading(heading);
+                continue;
+            }
+
+            match section {
+                Section::Preamble | Section::Tasks if metadata(trimmed).is_some() => {
+                    if let Some((key, value)) = metadata(trimmed) {
+                        doc.metadata.push((key.to_string(), value.to_string()));
+                    }
+                }
+                Section::Preamble if title.is_some() => preamble.push(line),
+                Section::Preamble | Section::Other => {}
+                Section::Description => description.push(line),
+                Section::Tasks => {
+                    if trimmed.is_empty() {
+                        continue;
+                    }
+                    if let Some(heading) = trimmed.strip_prefix("### ") {
+                        let m = DocumentMilestone::parse(heading, n)?;
+                        // A milestone's tasks may be listed under more than one heading.
+                        let existing = doc
+                            .milestones
+                            .iter()
+                            .position(|o| o.name == m.name && (m.id.is_none() || o.id == m.id));
+                        let index = existing.unwrap_or_else(|| {
+                            doc.milestones.push(m);
+                            doc.milestones.len() - 1
+                        });
+                        milestone = Some(index + 1);
+                        nesting.clear();
+                        continue;
+                    }
+                    let indent = line.len() - line.trim_start().len();
+                    match checkbox(trimmed) {
+                        Some((done, rest)) => {
+                            while nesting.last().is_some_and(|(i, _)| *i >= indent) {
+                                nesting.pop();
+                            }
+                            let mut task = DocumentTask::parse(rest, done, n)?;
+                            task.parent = nesting.last().map(|(_, parent)| parent + 1);
+                            task.milestone = milestone;
+
+                            nesting.push((indent, doc.tasks.len()));
+                            doc.tasks.push(task);
+                            lines.push(n);
+                        }
+                        None => {
+                            // Nested bullets and continuation lines describe the last task.
+                            let Some(task) = doc.tasks.last_mut() else {
+                                continue;
+                            };
+                            let text = bullet(trimmed).unwrap_or(trimmed);
+                            match &mut task.description {
+                                Some(d) => {
+                                    d.push('\n');
+                                    d.push_str(text);
+                                }
+                                None => task.description = Some(text.to_string()),
+                            }
+                        }
+                    }
+                }
+                Section::Issues | Section::Patches => {
+                    let Some(id) = bullet(trimmed).and_then(|b| b.split_whitespace().next()) else {
+                        continue;
+                    };
+                    let id = id.trim_matches('`').to_string();
+                    if section == Section::Issues {
+                        doc.issues.push(id);
+                    } else {
+                        doc.patches.push(id);
+                    }
+                }
+            }
+        }
+
+        for (i, (task, line)) in doc.tasks.iter().zip(&lines).enumerate() {
+            let invalid = |b: &&usize| **b == 0 || **b > doc.tasks.len() || **b == i + 1;
+            if let Some(b) = task.blocked_by.iter().find(invalid) {
+                return Err(DocumentError::InvalidBlocker(*line, b.to_string()));
+            }
+        }
+        doc.title = title.filter(|t| !t.is_empty()).ok_or(DocumentError::MissingTitle)?;
+        doc.description = [trim_lines(&preamble), trim_lines(&description)]
+            .into_iter()
+            .filter(|d| !d.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        Ok(doc)
+    }
+
+    /// Get the value of a metadata key, ignoring case.
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.metadata
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Get the values of a comma-separated metadata list, eg. `**Labels:** a, b`.
+    pub fn metadata_list(&self, key: &str) -> Vec<String> {
+        self.metadata(key)
+            .into_iter()
+            .flat_map(|v| v.split(','))
+            .map(|v| v.trim().trim_matches('`').to_string())
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
+
+    /// Resolve task blockers, given the IDs of the plan tasks created from this
+    /// document's tasks, in order. Returns each blocked task with its blockers.
+    pub fn blockers(&self, task_ids: &[TaskId]) -> Vec<(TaskId, Vec<TaskId>)> {
+        self.tasks
+            .iter()
+            .zip(task_ids)
+            .filter(|(t, _)| !t.blocked_by.is_empty())
+            .map(|(t, id)| {
+                let blockers = t.blocked_by.iter().filter_map(|b| task_ids.get(b.checked_sub(1)?)).copied();
+                (*id, blockers.collect())
+            })
+            .collect()
+    }
+
+    /// Resolve task parents, given the IDs of the plan tasks created from this
+    /// document's tasks, in order. Returns each subtask with its parent.
+    pub fn parents(&self, task_ids: &[TaskId]) -> Vec<(TaskId, TaskId)> {
+        self.tasks
+            .iter()
+            .zip(task_ids)
+            .filter_map(|(t, id)| Some((*id, *task_ids.get(t.parent?.checked_sub(1)?)?)))
+            .collect()
+    }
+
+    /// Resolve task milestones, given the IDs of the plan tasks and milestones created
+    /// from this document's tasks and milestones, in order. Returns each task that
+    /// belongs to a milestone with its milestone.
+    pub fn task_milestones(&self, task_ids: &[TaskId], milestone_ids: &[MilestoneId]) -> Vec<(TaskId, MilestoneId)> {
+        self.tasks
+            .iter()
+            .zip(task_ids)
+            .filter_map(|(t, id)| Some((*id, *milestone_ids.get(t.milestone?.checked_sub(1)?)?)))
+            .collect()
+    }
+
+    /// Compute the changes that make a plan match this document.
+    ///
+    /// Document tasks are matched to plan tasks by embedded task ID, then by subject.
+    /// Matched tasks are edited, unmatched document tasks are added, and unmatched plan
+    /// tasks are removed. Milestones are matched by ID, then by name, and synced the
+    /// same way. Only the title, description, milestones, tasks and their parents and
+    /// milestones are synced: linked objects, and the blockers and checked state of
+    /// tasks, are left as they are.
+    pub fn sync(&self, plan: &Plan) -> Result<DocumentSync, DocumentError> {
+        let mut matches: Vec<Option<TaskId>> = vec![None; self.tasks.len()];
+        let mut matched = BTreeSet::new();
+
+        // Match by ID first, so that renamed tasks keep their identity.
+        for (i, task) in self.tasks.iter().enumerate() {
+            let Some(prefix) = task.id.as_deref().filter(|p| !p.is_empty()) else {
+                continue;
+            };
+            let mut candidates = plan
+                .tasks()
+                .iter()
+                .filter(|t| !matched.contains(&t.id) && t.id.to_string().starts_with(prefix));
+
+            if let (Some(t), None) = (candidates.next(), candidates.next()) {
+                matches[i] = Some(t.id);
+                matched.insert(t.id);
+            }
+        }
+        for (i, task) in self.tasks.iter().enumerate() {
+            if matches[i].is_some() {
+                continue;
+            }
+            if let Some(t) = plan
+                .tasks()
+                .iter()
+                .find(|t| !matched.contains(&t.id) && t.subject == task.subject)
+            {
+                matches[i] = Some(t.id);
+                matched.insert(t.id);
+            }
+        }
+
+        let mut actions = Vec::new();
+        if self.title != plan.title() {
+            let title = Title::try_from(self.title.clone())
+                .map_err(|e| DocumentError::InvalidTitle(e.to_string()))?;
+            actions.push(Action::EditTitle { title });
+        }
+        if self.description != plan.description() {
+            actions.push(Action::EditDescription {
+                description: self.description.clone(),
+                embeds: vec![],
+            });
+        }
+
+        for (task, id) in self.tasks.iter().zip(&matches) {
+            if let Some(edit) = id.and_then(|id| plan.task(&id)).and_then(|t| task.edit(t)) {
+                actions.push(edit);
+            }
+        }
+        for t in plan.tasks().iter().filter(|t| !matched.contains(&t.id)) {
+            actions.push(Action::RemoveTask { task_id: t.id });
+        }
+
+        let mut added = 0;
+        let order = self
+            .tasks
+            .iter()
+            .zip(&matches)
+            .map(|(task, id)| match id {
+                Some(id) => TaskRef::Existing(*id),
+                None => {
+                    actions.push(Action::AddTask {
+                        subject: task.subject.clone(),
+                        description: task.description.clone(),
+                        estimate: task.estimate.clone(),
+                        affected_files: vec![],
+                    });
+                    added += 1;
+                    TaskRef::Added(added - 1)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Subtasks of removed tasks move up to the closest remaining ancestor.
+        let current_parent = |id: &TaskId| {
+            let mut parent = plan.task(id).and_then(|t| t.parent);
+            while let Some(p) = parent.filter(|p| !matched.contains(p)) {
+                parent = plan.task(&p).and_then(|t| t.parent);
+            }
+            parent.map(TaskRef::Existing)
+        };
+        let parent_of = |task: &DocumentTask| task.parent.and_then(|p| order.get(p.checked_sub(1)?)).copied();
+        let milestone_of = |r: &TaskRef| order.iter().position(|o| o == r).and_then(|i| self.tasks[i].milestone);
+        let parents = self
+            .tasks
+            .iter()
+            .zip(&order)
+            .filter_map(|(task, r)| {
+                let parent = parent_of(task);
+                let current = match r {
+                    TaskRef::Existing(id) => current_parent(id),
+                    TaskRef::Added(_) => None,
+                };
+                // A document can't nest a task under a parent in another milestone, so
+                // such parents are kept.
+                if parent.is_none() && current.is_some_and(|c| milestone_of(&c) != task.milestone) {
+                    return None;
+                }
+                (parent != current).then_some((*r, parent))
+            })
+            .collect::<Vec<_>>();
+
+        // Added tasks are appended after the remaining tasks, and tasks are listed as
+        // a tree within each milestone, so the document order is only kept if the plan
+        // lists the same way.
+        let tree = plan
+            .tasks()
+            .iter()
+            .filter(|t| matched.contains(&t.id))
+            .map(|t| TaskRef::Existing(t.id))
+            .chain((0..added).map(TaskRef::Added))
+            .map(|r| {
+                let i = order.iter().position(|o| *o == r);
+                (r, i.and_then(|i| parent_of(&self.tasks[i])))
+            })
+            .collect::<Vec<_>>();
+        let expected = (0..=self.milestones.len())
+            .flat_map(|g| {
+                let g = (g > 0).then_some(g);
+                let group = tree.iter().filter(|(r, _)| milestone_of(r) == g).copied().collect::<Vec<_>>();
+                tree_order(&group)
+            })
+            .map(|(_, r)| r)
+            .collect::<Vec<_>>();
+
+        let mut milestone_matches: Vec<Option<MilestoneId>> = vec![None; self.milestones.len()];
+        let mut matched_milestones = BTreeSet::new();
+
+        for (i, milestone) in self.milestones.iter().enumerate() {
+            let Some(prefix) = milestone.id.as_deref().filter(|p| !p.is_empty()) else {
+                continue;
+            };
+            let mut candidates = plan
+                .milestones_by_prefix(prefix)
+                .filter(|m| !matched_milestones.contains(&m.id));
+
+            if let (Some(m), None) = (candidates.next(), candidates.next()) {
+                milestone_matches[i] = Some(m.id);
+                matched_milestones.insert(m.id);
+            }
+        }
+        for (i, milestone) in self.milestones.iter().enumerate() {
+            if milestone_matches[i].is_some() {
+                continue;
+            }
+            if let Some(m) = plan
+                .milestones()
+                .iter()
+                .find(|m| !matched_milestones.contains(&m.id) && m.name == milestone.name)
+            {
+                milestone_matches[i] = Some(m.id);
+                matched_milestones.insert(m.id);
+            }
+        }
+
+        for (milestone, id) in self.milestones.iter().zip(&milestone_matches) {
+            let Some(m) = id.and_then(|id| plan.milestone(&id)) else {
+                continue;
+            };
+            let name = (milestone.name != m.name).then(|| milestone.name.clone());
+            let due = (milestone.due != m.due).then_some(milestone.due);
+            if name.is_some() || due.is_some() {
+                actions.push(Action::EditMilestone { milestone_id: m.id, name, due });
+            }
+        }
+        for m in plan.milestones().iter().filter(|m| !matched_milestones.contains(&m.id)) {
+            actions.push(Action::RemoveMilestone { milestone_id: m.id });
+        }
+
+        let mut added_milestones = 0;
+        let milestone_order = self
+            .milestones
+            .iter()
+            .zip(&milestone_matches)
+            .map(|(milestone, id)| match id {
+                Some(id) => MilestoneRef::Existing(*id),
+                None => {
+                    actions.push(Action::AddMilestone {
+                        name: milestone.name.clone(),
+                        due: milestone.due,
+                    });
+                    added_milestones += 1;
+                    MilestoneRef::Added(added_milestones - 1)
+                }
+            })
+            .collect::<Vec<_>>();
+        let expected_milestones = plan
+            .milestones()
+            .iter()
+            .filter(|m| matched_milestones.contains(&m.id))
+            .map(|m| MilestoneRef::Existing(m.id))
+            .chain((0..added_milestones).map(MilestoneRef::Added))
+            .collect::<Vec<_>>();
+
+        // Tasks of removed milestones are left without one.
+        let current_milestone = |id: &TaskId| {
+            let milestone = plan.task(id).and_then(|t| t.milestone);
+            milestone.filter(|m| matched_milestones.contains(m)).map(MilestoneRef::Existing)
+        };
+        let task_milestones = self
+            .tasks
+            .iter()
+            .zip(&order)
+            .filter_map(|(task, r)| {
+                let milestone = task.milestone.and_then(|m| milestone_order.get(m.checked_sub(1)?)).copied();
+                let current = match r {
+                    TaskRef::Existing(id) => current_milestone(id),
+                    TaskRef::Added(_) => None,
+                };
+                (milestone != current).then_some((*r, milestone))
+            })
+            .collect::<Vec<_>>();
+
+        Ok(DocumentSync {
+            actions,
+            order: (order != expected).then_some(order),
+            parents,
+            milestone_order: (milestone_order != expected_milestones).then_some(milestone_order),
+            task_milestones,
+        })
+    }
+}
+
+/// A reference to a task that exists, or will exist once a sync is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskRef {
+    /// A task of the plan.
+    Existing(TaskId),
+    /// The task created by the n-th `task.add` action of a sync.
+    Added(usize),
+}
+
+/// A reference to a milestone that exists, or will exist once a sync is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MilestoneRef {
+    /// A milestone of the plan.
+    Existing(MilestoneId),
+    /// The milestone created by the n-th `milestone.add` action of a sync.
+    Added(usize),
+}
+
+/// Changes that make a plan match a document. See [`PlanDocument::sync`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentSync {
+    /// Actions to apply, in a single change. Added tasks are appended to the plan.
+    pub actions: Vec<Action>,
+    /// Task order, if it differs from the order the plan has once `actions` are applied.
+    ///
+    /// Since the IDs of added tasks derive from the change that adds them, reordering
+    /// takes a second change. See [`DocumentSync::reorder`].
+    pub order: Option<Vec<TaskRef>>,
+    /// Tasks whose parent changes, with their new parent, in document order.
+    ///
+    /// Like the order, this takes a second change. See [`DocumentSync::set_parents`].
+    pub parents: Vec<(TaskRef, Option<TaskRef>)>,
+    /// Milestone order, if it differs from the order the plan has once `actions` are
+    /// applied. See [`DocumentSync::reorder_milestones`].
+    pub milestone_order: Option<Vec<MilestoneRef>>,
+    /// Tasks whose milestone changes, with their new milestone, in document order.
+    /// See [`DocumentSync::set_milestones`].
+    pub task_milestones: Vec<(TaskRef, Option<MilestoneRef>)>,
+}
+
+impl DocumentSync {
+    /// Whether the plan already matches the document.
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+            && self.order.is_none()
+            && self.parents.is_empty()
+            && self.milestone_order.is_none()
+            && self.task_milestones.is_empty()
+    }
+
+    /// Get the reorder action, given the change that applied [`DocumentSync::actions`].
+    pub fn reorder(&self, entry: EntryId) -> Option<Action> {
+        let order = self.order.as_ref()?;
+        let task_ids = order.iter().map(|r| r.resolve(entry)).collect();
+
+        Some(Action::ReorderTasks { task_ids })
+    }
+
+    /// Get the actions setting task parents, given the change that applied
+    /// [`DocumentSync::actions`]. Parents are set before their subtasks, so that no
+    /// intermediate state has a cycle.
+    pub fn set_parents(&self, entry: EntryId) -> Vec<Action> {
+        self.parents
+            .iter()
+            .map(|(r, parent)| Action::SetTaskParent {
+                task_id: r.resolve(entry),
+                parent: parent.map(|p| p.resolve(entry)),
+            })
+            .collect()
+    }
+
+    /// Get the milestone reorder action, given the change that applied
+    /// [`DocumentSync::actions`].
+    pub fn reorder_milestones(&self, entry: EntryId) -> Option<Action> {
+        let order = self.milestone_order.as_ref()?;
+        let milestone_ids = order.iter().map(|r| r.resolve(entry)).collect();
+
+        Some(Action::ReorderMilestones { milestone_ids })
+    }
+
+    /// Get the actions setting task milestones, given the change that applied
+    /// [`DocumentSync::actions`].
+    pub fn set_milestones(&self, entry: EntryId) -> Vec<Action> {
+        self.task_milestones
+            .iter()
+            .map(|(r, milestone)| Action::SetTaskMilestone {
+                task_id: r.resolve(entry),
+                milestone_id: milestone.map(|m| m.resolve(entry)),
+            })
+            .collect()
+    }
+}
+
+impl TaskRef {
+    /// Get the task ID, given the change that applied [`DocumentSync::actions`].
+    pub fn resolve(&self, entry: EntryId) -> TaskId {
+        match self {
+            Self::Existing(id) => *id,
+            Self::Added(n) => task_id(entry, *n),
+        }
+    }
+}
+
+impl MilestoneRef {
+    /// Get the milestone ID, given the change that applied [`DocumentSync::actions`].
+    pub fn resolve(&self, entry: EntryId) -> MilestoneId {
+        match self {
+            Self::Existing(id) => *id,
+            Self::Added(n) => milestone_id(entry, *n),
+        }
+    }
+}
+
+impl DocumentTask {
+    /// Create an unchecked task with the given subject.
+    pub fn new(subject: impl ToString) -> Self {
+        Self {
+            subject: subject.to_string(),
+            description: None,
+            estimate: None,
+            done: false,
+            id: None,
+            blocked_by: Vec::new(),
+            commit: None,
+            parent: None,
+            milestone: None,
+        }
+    }
+
+    /// Get the edit that makes a plan task match this one, if any.
+    fn edit(&self, task: &Task) -> Option<Action> {
+        let description = task.description.clone().filter(|d| !d.is_empty());
+
+        let subject = (self.subject != task.subject).then(|| self.subject.clone());
+        let description = (self.description != description).then(|| self.description.clone());
+        let estimate = (self.estimate != task.estimate).then(|| self.estimate.clone());
+
+        if subject.is_none() && description.is_none() && estimate.is_none() {
+            return None;
+        }
+        Some(Action::EditTask {
+            task_id: task.id,
+            subject,
+            description,
+            estimate,
+            affected_files: None,
+        })
+    }
+
+    /// Parse the text of a task line following its checkbox.
+    fn parse(text: &str, done: bool, line: usize) -> Result<Self, DocumentError> {
+        let mut text = text.trim().to_string();
+        let mut commit = None;
+        let mut id = None;
+        let mut blocked_by = Vec::new();
+
+        while let Some(start) = text.rfind("<!--") {
+            let Some(end) = text[start..].find("-->") else {
+                break;
+            };
+            let comment = text[start + 4..start + end].trim().to_string();
+            text.replace_range(start..start + end + 3, "");
+
+            if let Some(oid) = comment.strip_prefix("commit:") {
+                let oid = oid.trim();
+                commit = Some(
+                    Oid::from_str(oid).map_err(|_| DocumentError::InvalidCommit(line, oid.to_string()))?,
+                );
+            } else if let Some(task) = comment.strip_prefix("task:") {
+                id = Some(task.trim().to_lowercase());
+            } else if let Some(blockers) = comment.strip_prefix("blocked-by:") {
+                for b in blockers.split(|c: char| c == ',' || c.is_whitespace()).filter(|b| !b.is_empty()) {
+                    let n = b
+                        .trim_start_matches('#')
+                        .parse()
+                        .map_err(|_| DocumentError::InvalidBlocker(line, b.to_string()))?;
+                    blocked_by.push(n);
+                }
+            }
+            text = text.trim_end().to_string();
+        }
+
+        let mut estimate = None;
+        let trimmed = text.trim_end();
+        let without_underscore = trimmed.strip_suffix(")_").or_else(|| trimmed.strip_suffix(')'));
+        if let Some(start) = without_underscore.and_then(|t| t.rfind("_(").map(|i| (t, i))) {
+            let (t, i) = start;
+            estimate = Some(Estimate::lenient(t[i + 2..].trim()));
+            text = t[..i].to_string();
+        }
+
+        let subject = text.trim().to_string();
+        if subject.is_empty() {
+            return Err(DocumentError::EmptyTask(line));
+        }
+        Ok(Self {
+            subject,
+            description: None,
+            estimate,
+            done,
+            id,
+            blocked_by,
+            commit,
+            parent: None,
+            milestone: None,
+        })
+    }
+}
+
+impl DocumentMilestone {
+    /// Create a milestone with the given name.
+    pub fn new(name: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            due: None,
+            id: None,
+        }
+    }
+
+    /// Parse the text of a milestone heading following its `###`.
+    fn parse(text: &str, line: usize) -> Result<Self, DocumentError> {
+        let mut text = text.trim().to_string();
+        let mut id = None;
+
+        while let Some(start) = text.rfind("<!--") {
+            let Some(end) = text[start..].find("-->") else {
+                break;
+            };
+            let comment = text[start + 4..start + end].trim().to_string();
+            text.replace_range(start..start + end + 3, "");
+
+            if let Some(milestone) = comment.strip_prefix("milestone:") {
+                id = Some(milestone.trim().to_lowercase());
+            }
+            text = text.trim_end().to_string();
+        }
+
+        let mut due = None;
+        if let Some((name, date)) = text.strip_suffix(")_").and_then(|t| t.rsplit_once("_(due ")) {
+            let date = date.trim();
+            due = Some(date.parse().map_err(|_| DocumentError::InvalidDueDate(line, date.to_string()))?);
+            text = name.to_string();
+        }
+
+        let name = text.trim().to_string();
+        if name.is_empty() {
+            return Err(DocumentError::EmptyMilestone(line));
+        }
+        Ok(Self { name, due, id })
+    }
+}
+
+impl fmt::Display for DocumentMilestone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "### {}", self.name)?;
+        if let Some(due) = &self.due {
+            write!(f, " _(due {due})_")?;
+        }
+        if let Some(id) = &self.id {
+            write!(f, " <!-- milestone: {id} -->")?;
+        }
+        Ok(())
+    }
+}
+
+impl Section {
+    fn from_heading(heading: &str) -> Self {
+        // Headings may carry a count, eg. `## Tasks (3)`.
+        let name = match heading.trim().rsplit_once(" (") {
+            Some((name, count)) if count.trim_end_matches(')').chars().all(|c| c.is_ascii_digit()) => name,
+            _ => heading.trim(),
+        };
+        match name.to_lowercase().as_str() {
+            "description" => Self::Description,
+            "tasks" => Self::Tasks,
+            "linked issues" | "issues" => Self::Issues,
+            "linked patches" | "patches" => Self::Patches,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl FromStr for PlanDocument {
+    type Err = DocumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for PlanDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# {}", self.title)?;
+        writeln!(f)?;
+
+        if !self.metadata.is_empty() {
+            for (key, value) in &self.metadata {
+                writeln!(f, "**{key}:** {value}")?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.description.is_empty() {
+            writeln!(f, "## Description")?;
+            writeln!(f)?;
+            writeln!(f, "{}", self.description)?;
+            writeln!(f)?;
+        }
+
+        writeln!(f, "## Tasks ({})", self.tasks.len())?;
+        writeln!(f)?;
+        let mut depths: Vec<usize> = Vec::with_capacity(self.tasks.len());
+        let mut milestone = None;
+        let mut listed = false;
+        for task in &self.tasks {
+            if task.milestone != milestone {
+                if let Some(m) = task.milestone.and_then(|m| self.milestones.get(m.checked_sub(1)?)) {
+                    if listed {
+                        writeln!(f)?;
+                    }
+                    writeln!(f, "{m}")?;
+                    writeln!(f)?;
+                }
+                milestone = task.milestone;
+            }
+            listed = true;
+
+            let depth = task
+                .parent
+                .and_then(|p| depths.get(p.checked_sub(1)?))
+                .map_or(0, |d| d + 1);
+            depths.push(depth);
+
+            let indent = "  ".repeat(depth);
+            let checkbox = if task.done { "[x]" } else { "[ ]" };
+            write!(f, "{indent}- {checkbox} {}", task.subject)?;
+            if let Some(estimate) = &task.estimate {
+                write!(f, " _({estimate})_")?;
+            }
+            if let Some(id) = &task.id {
+                write!(f, " <!-- task: {id} -->")?;
+            }
+            if !task.blocked_by.is_empty() {
+                let blockers = task.blocked_by.iter().map(|b| b.to_string()).collect::<Vec<_>>();
+                write!(f, " <!-- blocked-by: {} -->", blockers.join(", "))?;
+            }
+            if let Some(commit) = &task.commit {
+                write!(f, " <!-- commit: {commit} -->")?;
+            }
+            writeln!(f)?;
+
+            for line in task.description.iter().flat_map(|d| d.lines()) {
+                writeln!(f, "{indent}  - {line}")?;
+            }
+        }
+        // Milestones without tasks are listed last.
+        for (i, m) in self.milestones.iter().enumerate() {
+            if !self.tasks.iter().any(|t| t.milestone == Some(i + 1)) {
+                if listed {
+                    writeln!(f)?;
+                }
+                writeln!(f, "{m}")?;
+                listed = true;
+            }
+        }
+
+        for (heading, ids) in [("Linked Issues", &self.issues), ("Linked Patches", &self.patches)] {
+            if !ids.is_empty() {
+                writeln!(f)?;
+                writeln!(f, "## {heading}")?;
+                writeln!(f)?;
+                for id in ids {
+                    writeln!(f, "- {id}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse a `**Key:** value` line.
+fn metadata(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("**")?;
+    let (key, value) = rest.split_once(":**")?;
+
+    Some((key.trim(), value.trim()))
+}
+
+/// Parse a `- [ ] ` or `- [x] ` checklist item, returning whether it is checked.
+fn checkbox(line: &str) -> Option<(bool, &str)> {
+    let rest = bullet(line)?;
+    let (mark, rest) = rest.strip_prefix('[')?.split_once(']')?;
+    let done = match mark {
+        " " | "" => false,
+        "x" | "X" => true,
+        _ => return None,
+    };
+    Some((done, rest))
+}
+
+/// Strip a list bullet from a line.
+fn bullet(line: &str) -> Option<&str> {
+    line.strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| (line == "-" || line == "*").then_some(""))
+}
+
+/// Join lines, dropping leading and trailing blank lines.
+fn trim_lines(lines: &[&str]) -> String {
+    let start = lines.iter().position(|l| !l.trim().is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !l.trim().is_empty()).map_or(start, |i| i + 1);
+
+    lines[start..end].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = "\
+# Implement auth
+
+**Status:** in-progress
+**Author:** did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK
+
+## Description
+
+Add authentication.
+
+Sessions are stored server-side.
+
+## Tasks (3)
+
+**Estimate:** 1d 2h
+
+- [ ] Design the schema _(2h)_
+  - Users and sessions
+  - Keep it minimal
+- [x] Write middleware _(1d)_ <!-- commit: 0123456789abcdef0123456789abcdef01234567 -->
+- [ ] Document it <!-- blocked-by: 1, 2 -->
+
+## Linked Issues
+
+- 108a1dc
+
+## Linked Patches
+
+- `aabb123` (draft)
+";
+
+    #[test]
+    fn test_parse() {
+        let doc = PlanDocument::parse(DOCUMENT).unwrap();
+
+        assert_eq!(doc.title, "Implement auth");
+        assert_eq!(doc.description, "Add authentication.\n\nSessions are stored server-side.");
+        assert_eq!(doc.metadata("status"), Some("in-progress"));
+        assert_eq!(doc.metadata("Estimate"), Some("1d 2h"));
+        assert_eq!(doc.issues, vec!["108a1dc"]);
+        assert_eq!(doc.patches, vec!["aabb123"]);
+
+        let subjects = doc.tasks.iter().map(|t| t.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["Design the schema", "Write middleware", "Document it"]);
+
+        let design = &doc.tasks[0];
+        assert_eq!(design.description.as_deref(), Some("Users and sessions\nKeep it minimal"));
+        assert_eq!(design.estimate.as_ref().and_then(Estimate::minutes), Some(120));
+        assert!(!design.done);
+
+        let middleware = &doc.tasks[1];
+        assert!(middleware.done);
+        assert!(middleware.commit.is_some());
+        assert_eq!(middleware.estimate.as_ref().map(Estimate::as_str), Some("1d"));
+        assert_eq!(doc.tasks[2].blocked_by, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        let doc = PlanDocument::parse(DOCUMENT).unwrap();
+        let rendered = PlanDocument::parse(&doc.to_string()).unwrap();
+
+        assert_eq!(rendered, doc);
+    }
+
+    #[test]
+    fn test_preamble_is_description() {
+        let doc = PlanDocument::parse("# Title\n\nSome context.\n\n- [ ] Not a task\n").unwrap();
+
+        assert_eq!(doc.description, "Some context.\n\n- [ ] Not a task");
+        assert!(doc.tasks.is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(PlanDocument::parse("## Tasks\n"), Err(DocumentError::MissingTitle));
+        assert_eq!(
+            PlanDocument::parse("# Title\n\n## Tasks\n\n- [ ] _(2h)_\n"),
+            Err(DocumentError::EmptyTask(5))
+        );
+        assert_eq!(
+            PlanDocument::parse("# Title\n\n## Tasks\n\n- [ ] A\n- [ ] B <!-- blocked-by: 3 -->\n"),
+            Err(DocumentError::InvalidBlocker(6, "3".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_sync() {
+        use crate::state::test;
+
+        let id = |n: u8| Oid::from_str(&format!("{n:x}{}", "0".repeat(39))).unwrap();
+        let mut plan = test::plan(id(15));
+        for (n, subject) in [(1, "A"), (2, "B"), (3, "C")] {
+            plan.push_task(id(n), subject);
+        }
+
+        let doc = PlanDocument::parse(
+            "# Plan\n\n## Tasks\n\n- [ ] C\n- [ ] Renamed <!-- task: 2000000 -->\n- [ ] New _(1h)_\n",
+        )
+        .unwrap();
+        let sync = doc.sync(&plan).unwrap();
+
+        assert_eq!(
+            sync.actions,
+            vec![
+                Action::EditTask {
+                    task_id: id(2),
+                    subject: Some("Renamed".to_string()),
+                    description: None,
+                    estimate: None,
+                    affected_files: None,
+                },
+                Action::RemoveTask { task_id: id(1) },
+                Action::AddTask {
+                    subject: "New".to_string(),
+                    description: None,
+                    estimate: Some(Estimate::from_minutes(60)),
+                    affected_files: vec![],
+                },
+            ]
+        );
+        assert_eq!(
+            sync.reorder(id(9)),
+            Some(Action::ReorderTasks { task_ids: vec![id(3), id(2), id(9)] })
+        );
+
+        // Syncing a plan's own document is a no-op.
+        assert!(PlanDocument::from_plan(&plan).sync(&plan).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_nested_tasks() {
+        let doc = PlanDocument::parse(
+            "# Plan\n\n## Tasks\n\n- [ ] A\n  - About A\n  - [ ] A.1\n    - [x] A.1.1\n  - [ ] A.2\n- [ ] B\n",
+        )
+        .unwrap();
+
+        let parents = doc.tasks.iter().map(|t| t.parent).collect::<Vec<_>>();
+        assert_eq!(parents, vec![None, Some(1), Some(2), Some(1), None]);
+        assert_eq!(doc.tasks[0].description.as_deref(), Some("About A"));
+        assert!(doc.to_string().contains("\n    - [x] A.1.1\n"));
+        assert_eq!(PlanDocument::parse(&doc.to_string()).unwrap(), doc);
+    }
+
+    #[test]
+    fn test_milestones() {
+        use radicle::cob::common::Timestamp;
+
+        use crate::milestone::Milestone;
+        use crate::state::test;
+
+        let doc = PlanDocument::parse(
+            "# Plan\n\n## Tasks\n\n- [ ] A\n\n### Alpha _(due 2030-01-31)_ <!-- milestone: abc1234 -->\n\n\
+             - [ ] B\n  - [ ] B.1\n\n### Beta\n",
+        )
+        .unwrap();
+
+        let milestones = doc.tasks.iter().map(|t| t.milestone).collect::<Vec<_>>();
+        assert_eq!(milestones, vec![None, Some(1), Some(1)]);
+        assert_eq!(doc.tasks[2].parent, Some(2));
+        assert_eq!(doc.milestones[0].due.map(|d| d.to_string()).as_deref(), Some("2030-01-31"));
+        assert_eq!(doc.milestones[0].id.as_deref(), Some("abc1234"));
+        assert_eq!(doc.milestones[1], DocumentMilestone::new("Beta"));
+        assert_eq!(PlanDocument::parse(&doc.to_string()).unwrap(), doc);
+        assert_eq!(
+            PlanDocument::parse("# Plan\n\n## Tasks\n\n### Alpha _(due soon)_\n"),
+            Err(DocumentError::InvalidDueDate(5, "soon".to_string()))
+        );
+
+        let id = |n: u8| Oid::from_str(&format!("{n:x}{}", "0".repeat(39))).unwrap();
+        let mut plan = test::plan(id(15));
+        plan.milestones.push(Milestone::new(id(10), "Alpha".to_string(), None, test::author(), Timestamp::from_secs(0)));
+        for (n, subject) in [(1, "A"), (2, "B"), (3, "C")] {
+            plan.push_task(id(n), subject);
+        }
+        // B is a subtask of A, but in another milestone.
+        plan.tasks[1].parent = Some(id(1));
+        plan.tasks[1].milestone = Some(id(10));
+
+        let doc = PlanDocument::from_plan(&plan);
+        let subjects = doc.tasks.iter().map(|t| t.subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["A", "C", "B"]);
+        assert_eq!(doc.tasks[2].parent, None);
+        assert!(doc.sync(&plan).unwrap().is_empty());
+
+        // Moving C under Alpha, and adding a milestone.
+        let doc = PlanDocument::parse(
+            "# Plan\n\n## Tasks\n\n- [ ] A\n\n### Alpha\n\n- [ ] B\n- [ ] C\n\n### Gamma\n\n- [ ] D\n",
+        )
+        .unwrap();
+        let sync = doc.sync(&plan).unwrap();
+        assert!(sync.actions.contains(&Action::AddMilestone { name: "Gamma".to_string(), due: None }));
+        assert!(sync.parents.is_empty());
+        assert_eq!(
+            sync.set_milestones(id(9)),
+            vec![
+                Action::SetTaskMilestone { task_id: id(3), milestone_id: Some(id(10)) },
+                Action::SetTaskMilestone { task_id: task_id(id(9), 0), milestone_id: Some(milestone_id(id(9), 0)) },
+            ]
+        );
+    }
+}
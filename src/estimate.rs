@@ -0,0 +1,307 @@
+//! Task estimates.
+//!
+//! Estimates are written as a duration, story points, or both, eg. `"2h"`,
+//! `"1d 4h"`, `"3 days"`, `"5sp"` or `"1w 3pt"`. A day is eight hours of work
+//! and a week is five days.
+//!
+//! On the wire, an estimate is the string it was written as, so that estimates
+//! stored before they were typed still deserialize. Strings that can't be parsed
+//! are kept verbatim, but don't count towards totals.
+
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+/// Minutes in a working hour.
+pub const HOUR: u64 = 60;
+/// Minutes in a working day.
+pub const DAY: u64 = 8 * HOUR;
+/// Minutes in a working week.
+pub const WEEK: u64 = 5 * DAY;
+
+/// Error parsing an estimate.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum EstimateError {
+    /// The estimate is empty.
+    #[error("estimate is empty")]
+    Empty,
+    /// An amount is missing or not a number.
+    #[error("invalid amount in estimate '{0}'")]
+    InvalidAmount(String),
+    /// An amount, or the total of the amounts, is too large.
+    #[error("amount out of range in estimate '{0}'")]
+    OutOfRange(String),
+    /// A unit is missing or unknown.
+    #[error("unknown unit '{0}' in estimate: expected m, h, d, w or sp")]
+    InvalidUnit(String),
+}
+
+/// An estimate of the work needed for a task.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Estimate {
+    /// Estimated duration, in minutes of work.
+    minutes: Option<u64>,
+    /// Estimated story points.
+    points: Option<u32>,
+    /// The estimate as written.
+    text: String,
+}
+
+impl Estimate {
+    /// Create an estimate from a duration and story points.
+    pub fn new(minutes: Option<u64>, points: Option<u32>) -> Self {
+        let mut parts = Vec::new();
+
+        if let Some(mut rest) = minutes {
+            for (unit, suffix) in [(WEEK, "w"), (DAY, "d"), (HOUR, "h"), (1, "m")] {
+                if rest >= unit {
+                    parts.push(format!("{}{suffix}", rest / unit));
+                    rest %= unit;
+                }
+            }
+            if parts.is_empty() {
+                parts.push("0h".to_string());
+            }
+        }
+        if let Some(points) = points {
+            parts.push(format!("{points}sp"));
+        }
+
+        Self {
+            minutes,
+            points,
+            text: parts.join(" "),
+        }
+    }
+
+    /// Create an estimate from a duration in minutes.
+    pub fn from_minutes(minutes: u64) -> Self {
+        Self::new(Some(minutes), None)
+    }
+
+    /// Parse an estimate, failing on anything that isn't understood.
+    pub fn parse(s: &str) -> Result<Self, EstimateError> {
+        let text = s.trim();
+        let mut minutes: Option<f64> = None;
+        let mut points: Option<u32> = None;
+        let mut chars = text.chars().peekable();
+
+        if text.is_empty() {
+            return Err(EstimateError::Empty);
+        }
+        while chars.peek().is_some() {
+            let amount = take_while(&mut chars, |c| c.is_ascii_digit() || c == '.');
+            take_while(&mut chars, char::is_whitespace);
+            let unit = take_while(&mut chars, char::is_alphabetic).to_lowercase();
+            take_while(&mut chars, |c| c.is_whitespace() || c == ',' || c == '+');
+
+            let value: f64 = amount
+                .parse()
+                .map_err(|_| EstimateError::InvalidAmount(text.to_string()))?;
+            let unit = match unit.as_str() {
+                "m" | "min" | "mins" | "minute" | "minutes" => 1,
+                "h" | "hr" | "hrs" | "hour" | "hours" => HOUR,
+                "d" | "day" | "days" => DAY,
+                "w" | "wk" | "wks" | "week" | "weeks" => WEEK,
+                "sp" | "pt" | "pts" | "point" | "points" => {
+                    let value = amount.parse::<u32>().map_err(|e| match e.kind() {
+                        std::num::IntErrorKind::PosOverflow => EstimateError::OutOfRange(text.to_string()),
+                        _ => EstimateError::InvalidAmount(text.to_string()),
+                    })?;
+                    let total = points.unwrap_or(0).checked_add(value);
+                    points = Some(total.ok_or_else(|| EstimateError::OutOfRange(text.to_string()))?);
+                    continue;
+                }
+                _ => return Err(EstimateError::InvalidUnit(unit)),
+            };
+            let total = minutes.unwrap_or(0.0) + value * unit as f64;
+            // Above this, minutes no longer round-trip through `u64`.
+            if !total.is_finite() || total >= u64::MAX as f64 {
+                return Err(EstimateError::OutOfRange(text.to_string()));
+            }
+            minutes = Some(total);
+        }
+
+        Ok(Self {
+            minutes: minutes.map(|m| m.round() as u64),
+            points,
+            text: text.to_string(),
+        })
+    }
+
+    /// Parse an estimate, keeping strings that can't be parsed as opaque text.
+    pub fn lenient(s: &str) -> Self {
+        Self::parse(s).unwrap_or_else(|_| Self {
+            minutes: None,
+            points: None,
+            text: s.to_string(),
+        })
+    }
+
+    /// Estimated duration, in minutes of work.
+    pub fn minutes(&self) -> Option<u64> {
+        self.minutes
+    }
+
+    /// Estimated duration, in hours of work.
+    pub fn hours(&self) -> Option<f64> {
+        self.minutes.map(|m| m as f64 / HOUR as f64)
+    }
+
+    /// Estimated story points.
+    pub fn points(&self) -> Option<u32> {
+        self.points
+    }
+
+    /// Whether the estimate was understood. Opaque legacy strings are not.
+    pub fn is_parsed(&self) -> bool {
+        self.minutes.is_some() || self.points.is_some()
+    }
+
+    /// The estimate as written.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Add for Estimate {
+    type Output = Estimate;
+
+    fn add(self, other: Self) -> Self::Output {
+        let minutes = match (self.minutes, other.minutes) {
+            (Some(a), Some(b)) => Some(a.saturating_add(b)),
+            (a, b) => a.or(b),
+        };
+        let points = match (self.points, other.points) {
+            (Some(a), Some(b)) => Some(a.saturating_add(b)),
+            (a, b) => a.or(b),
+        };
+        Self::new(minutes, points)
+    }
+}
+
+impl std::iter::Sum for Estimate {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |acc, e| acc + e)
+    }
+}
+
+impl<'a> std::iter::Sum<&'a Estimate> for Estimate {
+    fn sum<I: Iterator<Item = &'a Estimate>>(iter: I) -> Self {
+        iter.cloned().sum()
+    }
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl FromStr for Estimate {
+    type Err = EstimateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Serialize for Estimate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for Estimate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Ok(Self::lenient(&text))
+    }
+}
+
+/// Consume characters while they match a predicate.
+fn take_while(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    predicate: impl Fn(char) -> bool,
+) -> String {
+    let mut out = String::new();
+    while let Some(c) = chars.next_if(|c| predicate(*c)) {
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_durations() {
+        assert_eq!(Estimate::parse("2h").unwrap().minutes(), Some(120));
+        assert_eq!(Estimate::parse("1.5d").unwrap().minutes(), Some(720));
+        assert_eq!(Estimate::parse("1w 2d").unwrap().minutes(), Some(3360));
+        assert_eq!(Estimate::parse("3 days").unwrap().minutes(), Some(3 * DAY));
+        assert_eq!(Estimate::parse("30min").unwrap().minutes(), Some(30));
+        assert_eq!(Estimate::parse("1D").unwrap().minutes(), Some(DAY));
+    }
+
+    #[test]
+    fn test_parse_story_points() {
+        let estimate = Estimate::parse("1d 5sp").unwrap();
+        assert_eq!(estimate.minutes(), Some(DAY));
+        assert_eq!(estimate.points(), Some(5));
+
+        let estimate = Estimate::parse("3 points").unwrap();
+        assert_eq!(estimate.minutes(), None);
+        assert_eq!(estimate.points(), Some(3));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Estimate::parse(" "), Err(EstimateError::Empty));
+        assert!(matches!(Estimate::parse("soon"), Err(EstimateError::InvalidAmount(_))));
+        assert!(matches!(Estimate::parse("2 parsecs"), Err(EstimateError::InvalidUnit(_))));
+        assert!(matches!(Estimate::parse("1.5sp"), Err(EstimateError::InvalidAmount(_))));
+        assert!(matches!(
+            Estimate::parse("3000000000sp 3000000000sp"),
+            Err(EstimateError::OutOfRange(_))
+        ));
+        assert!(matches!(Estimate::parse("5000000000sp"), Err(EstimateError::OutOfRange(_))));
+        assert!(matches!(
+            Estimate::parse("99999999999999999999w"),
+            Err(EstimateError::OutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_legacy_strings_deserialize() {
+        let estimate: Estimate = serde_json::from_str(r#""2h""#).unwrap();
+        assert_eq!(estimate.minutes(), Some(120));
+
+        let estimate: Estimate = serde_json::from_str(r#""a couple of days""#).unwrap();
+        assert!(!estimate.is_parsed());
+        assert_eq!(serde_json::to_string(&estimate).unwrap(), r#""a couple of days""#);
+    }
+
+    #[test]
+    fn test_sum() {
+        let total: Estimate = ["2h", "1d", "3sp", "unknown"]
+            .into_iter()
+            .map(Estimate::lenient)
+            .sum();
+
+        assert_eq!(total.minutes(), Some(10 * HOUR));
+        assert_eq!(total.points(), Some(3));
+        assert_eq!(total.to_string(), "1d 2h 3sp");
+        assert_eq!(Estimate::from_minutes(0).to_string(), "0h");
+
+        // Totals saturate rather than overflow.
+        let huge = Estimate::new(Some(u64::MAX - 1), Some(u32::MAX));
+        let total: Estimate = [huge.clone(), huge].iter().sum();
+        assert_eq!(total.minutes(), Some(u64::MAX));
+        assert_eq!(total.points(), Some(u32::MAX));
+    }
+}
@@ -0,0 +1,245 @@
+//! Task dependency graph analysis.
+//!
+//! Tasks form a directed acyclic graph through [`Task::blocked_by`]. This module
+//! orders tasks topologically and computes a critical path schedule over the
+//! remaining work, using task estimates as durations.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::estimate::Estimate;
+use crate::state::{Plan, Task, TaskId};
+
+/// Scheduling information for a single task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSchedule {
+    /// Task identifier.
+    pub id: TaskId,
+    /// Number of tasks on the longest dependency chain leading up to this task.
+    pub depth: usize,
+    /// Remaining work on this task, in minutes. Zero for done or unestimated tasks.
+    pub duration: u64,
+    /// Earliest time the task can start, in minutes of work after the plan start.
+    pub earliest_start: u64,
+    /// Earliest time the task can finish, in minutes of work after the plan start.
+    pub earliest_finish: u64,
+    /// How many minutes the task can slip without delaying plan completion.
+    pub slack: u64,
+}
+
+impl TaskSchedule {
+    /// Whether the task is on the critical path.
+    pub fn is_critical(&self) -> bool {
+        self.slack == 0
+    }
+}
+
+impl Plan {
+    /// Get all tasks ordered so that every task comes after the tasks blocking it.
+    ///
+    /// Tasks that are not ordered relative to each other keep their stored order.
+    pub fn topological_order(&self) -> Vec<&Task> {
+        let mut indegree: BTreeMap<TaskId, usize> = BTreeMap::new();
+        let mut dependents: BTreeMap<TaskId, Vec<TaskId>> = BTreeMap::new();
+
+        for task in &self.tasks {
+            let blockers = task
+                .blocked_by
+                .iter()
+                .filter(|b| self.task(b).is_some())
+                .collect::<BTreeSet<_>>();
+
+            indegree.insert(task.id, blockers.len());
+            for blocker in blockers {
+                dependents.entry(*blocker).or_default().push(task.id);
+            }
+        }
+
+        let mut order = Vec::with_capacity(self.tasks.len());
+        let mut visited = BTreeSet::new();
+
+        while order.len() < self.tasks.len() {
+            // Pick the first ready task in stored order, so the result is stable.
+            let next = self
+                .tasks
+                .iter()
+                .find(|t| !visited.contains(&t.id) && indegree.get(&t.id) == Some(&0))
+                // Dependency cycles are rejected during evaluation, but fall back to
+                // stored order rather than dropping tasks if one is ever present.
+                .or_else(|| self.tasks.iter().find(|t| !visited.contains(&t.id)));
+
+            let Some(task) = next else {
+                break;
+            };
+            visited.insert(task.id);
+            order.push(task);
+
+            for dependent in dependents.get(&task.id).into_iter().flatten() {
+                if let Some(n) = indegree.get_mut(dependent) {
+                    *n = n.saturating_sub(1);
+                }
+            }
+        }
+        order
+    }
+
+    /// Compute a schedule of the remaining work, in topological order.
+    ///
+    /// Each task's duration is its estimate, or zero if the task is done or has no
+    /// parseable estimate. Tasks with zero slack are on the critical path.
+    pub fn schedule(&self) -> Vec<TaskSchedule> {
+        let order = self.topological_order();
+        let mut schedule: Vec<TaskSchedule> = Vec::with_capacity(order.len());
+        let mut index: BTreeMap<TaskId, usize> = BTreeMap::new();
+
+        for task in &order {
+            let duration = remaining_minutes(task);
+            let blockers = task
+                .blocked_by
+                .iter()
+                .filter_map(|b| index.get(b))
+                .map(|i| &schedule[*i])
+                .collect::<Vec<_>>();
+            let earliest_start = blockers.iter().map(|s| s.earliest_finish).max().unwrap_or(0);
+            let depth = blockers.iter().map(|s| s.depth + 1).max().unwrap_or(0);
+
+            index.insert(task.id, schedule.len());
+            schedule.push(TaskSchedule {
+                id: task.id,
+                depth,
+                duration,
+                earliest_start,
+                earliest_finish: earliest_start + duration,
+                slack: 0,
+            });
+        }
+
+        // Walk backwards to find how late each task can finish without delaying the plan.
+        let end = schedule.iter().map(|s| s.earliest_finish).max().unwrap_or(0);
+        let mut latest_finish = vec![end; schedule.len()];
+
+        for (i, task) in order.iter().enumerate().rev() {
+            let latest_start = latest_finish[i] - schedule[i].duration;
+            schedule[i].slack = latest_start - schedule[i].earliest_start;
+
+            for blocker in &task.blocked_by {
+                if let Some(j) = index.get(blocker) {
+                    latest_finish[*j] = latest_finish[*j].min(latest_start);
+                }
+            }
+        }
+        schedule
+    }
+
+    /// Get the chain of tasks that gates plan completion, from first to last.
+    ///
+    /// This is the dependency chain with the most remaining estimated work. Ties,
+    /// including plans without estimates, are broken by the longest chain of tasks.
+    pub fn critical_path(&self) -> Vec<&Task> {
+        let schedule = self.schedule();
+        let index = schedule
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.id, i))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut path = Vec::new();
+        let mut current = schedule
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, s)| (s.earliest_finish, s.depth, std::cmp::Reverse(*i)))
+            .map(|(i, _)| i);
+
+        while let Some(i) = current {
+            let Some(task) = self.task(&schedule[i].id) else {
+                break;
+            };
+            path.push(task);
+
+            current = task
+                .blocked_by
+                .iter()
+                .filter_map(|b| index.get(b).copied())
+                .filter(|j| schedule[*j].earliest_finish == schedule[i].earliest_start)
+                .max_by_key(|j| (schedule[*j].depth, std::cmp::Reverse(*j)));
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// Remaining work on a task, in minutes.
+fn remaining_minutes(task: &Task) -> u64 {
+    if task.is_done() {
+        return 0;
+    }
+    task.estimate.as_ref().and_then(Estimate::minutes).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    use radicle::git::Oid;
+
+    use crate::state::test;
+
+    fn id(n: u8) -> TaskId {
+        Oid::from_str(&format!("{n:040x}")).unwrap()
+    }
+
+    fn plan(tasks: &[(u8, Option<&str>, &[u8])]) -> Plan {
+        let mut plan = test::plan(id(0));
+        for (n, estimate, blockers) in tasks {
+            let task = plan.push_task(id(*n), format!("Task {n}"));
+            if let Some(estimate) = estimate {
+                task.estimated(estimate);
+            }
+            task.blocked_by = blockers.iter().copied().map(id).collect();
+        }
+        plan
+    }
+
+    #[test]
+    fn test_topological_order_respects_blockers() {
+        // Stored order lists dependents before their blockers.
+        let p = plan(&[(3, None, &[2]), (2, None, &[1]), (4, None, &[]), (1, None, &[])]);
+        let order = p.topological_order().iter().map(|t| t.id).collect::<Vec<_>>();
+
+        assert_eq!(order, vec![id(4), id(1), id(2), id(3)]);
+    }
+
+    #[test]
+    fn test_critical_path_follows_longest_estimate() {
+        // 1 -> 2 -> 4 takes 2h + 1h + 1h, 1 -> 3 -> 4 takes 2h + 1d + 1h.
+        let p = plan(&[
+            (1, Some("2h"), &[]),
+            (2, Some("1h"), &[1]),
+            (3, Some("1d"), &[1]),
+            (4, Some("1h"), &[2, 3]),
+        ]);
+        let path = p.critical_path().iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(path, vec![id(1), id(3), id(4)]);
+
+        let schedule = p.schedule();
+        let task2 = schedule.iter().find(|s| s.id == id(2)).unwrap();
+        assert_eq!(task2.depth, 1);
+        assert_eq!(task2.slack, 7 * 60);
+        assert!(!task2.is_critical());
+
+        let task4 = schedule.iter().find(|s| s.id == id(4)).unwrap();
+        assert_eq!(task4.depth, 2);
+        assert_eq!(task4.earliest_finish, 11 * 60);
+    }
+
+    #[test]
+    fn test_critical_path_without_estimates_is_longest_chain() {
+        let p = plan(&[(1, None, &[]), (2, None, &[]), (3, None, &[2])]);
+        let path = p.critical_path().iter().map(|t| t.id).collect::<Vec<_>>();
+
+        assert_eq!(path, vec![id(2), id(3)]);
+    }
+}
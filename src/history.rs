@@ -0,0 +1,150 @@
+//! Plan history.
+//!
+//! A plan's history is every action recorded in its change graph, in the order the
+//! changes are applied during evaluation. Actions are decoded and attributed to the
+//! author of the change that carries them. Actions that were rejected during
+//! evaluation, eg. because their author wasn't authorized, are included as recorded.
+//!
+//! The same changes can be re-evaluated up to any point in the graph, to get the
+//! state of a plan as of a past change.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use radicle::cob;
+use radicle::cob::common::Timestamp;
+use radicle::cob::store::Cob as _;
+use radicle::cob::EntryId;
+use radicle::prelude::{Did, ReadRepository};
+
+use crate::{milestone_id, task_id, Action, Created, Error, MilestoneId, Op, Plan, TaskId};
+
+/// A single action in a plan's history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanEvent {
+    /// Change entry the action was recorded in.
+    pub entry: EntryId,
+    /// Author of the change.
+    pub author: Did,
+    /// When the change was made.
+    pub timestamp: Timestamp,
+    /// For `task.add`, the ID of the task that was created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<TaskId>,
+    /// For `milestone.add`, the ID of the milestone that was created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milestone: Option<MilestoneId>,
+    /// The action.
+    pub action: Action,
+}
+
+/// Collects the changes of a plan while its change graph is evaluated.
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    ops: Vec<Op>,
+}
+
+impl History {
+    /// Get the recorded actions as events, in evaluation order.
+    pub(crate) fn into_events(self) -> Vec<PlanEvent> {
+        let mut events = Vec::new();
+
+        for op in self.ops {
+            let mut created = Created::default();
+            for action in op.actions {
+                let index = created.index(&action);
+                let task = matches!(action, Action::AddTask { .. }).then(|| task_id(op.id, index));
+                let milestone = matches!(action, Action::AddMilestone { .. }).then(|| milestone_id(op.id, index));
+                events.push(PlanEvent {
+                    entry: op.id,
+                    author: op.author.into(),
+                    timestamp: op.timestamp,
+                    task,
+                    milestone,
+                    action,
+                });
+            }
+        }
+        events
+    }
+
+    /// Evaluate the plan from the changes up to and including the given one.
+    ///
+    /// Returns `None` if the change isn't part of the plan. Changes that fail to apply
+    /// are skipped, as they are when the whole graph is evaluated.
+    pub(crate) fn evaluate_at<R: ReadRepository>(
+        self,
+        until: &EntryId,
+        repo: &R,
+    ) -> Result<Option<Plan>, Error> {
+        // Changes are recorded in topological order, so parents are seen first.
+        let mut ancestors: BTreeMap<EntryId, BTreeSet<EntryId>> = BTreeMap::new();
+        for op in &self.ops {
+            let mut set = BTreeSet::new();
+            for parent in &op.parents {
+                set.insert(*parent);
+                set.extend(ancestors.get(parent).into_iter().flatten().copied());
+            }
+            ancestors.insert(op.id, set);
+        }
+        let Some(included) = ancestors.get(until) else {
+            return Ok(None);
+        };
+
+        let mut plan: Option<Plan> = None;
+        let mut applied: Vec<EntryId> = Vec::new();
+
+        for op in self.ops.into_iter().filter(|op| op.id == *until || included.contains(&op.id)) {
+            let id = op.id;
+            let Some(current) = plan.as_mut() else {
+                plan = Some(Plan::from_root(op, repo)?);
+                applied.push(id);
+                continue;
+            };
+            let concurrent = applied
+                .iter()
+                .filter(|e| !ancestors[&id].contains(e))
+                .copied()
+                .collect::<BTreeSet<_>>();
+
+            let mut next = current.clone();
+            match next.apply_op(op, &concurrent, repo) {
+                Ok(()) => {
+                    *current = next;
+                    applied.push(id);
+                }
+                Err(e) => {
+                    log::warn!(target: "plan", "Skipping change {id} that failed to apply: {e}");
+                }
+            }
+        }
+        Ok(plan)
+    }
+
+    fn push(&mut self, entry: &cob::Entry) -> Result<(), Error> {
+        self.ops.push(Op::try_from(entry)?);
+        Ok(())
+    }
+}
+
+impl<R> cob::Evaluate<R> for History {
+    type Error = Error;
+
+    fn init(entry: &cob::Entry, _repo: &R) -> Result<Self, Self::Error> {
+        let mut history = Self::default();
+        history.push(entry)?;
+
+        Ok(history)
+    }
+
+    fn apply<'a, I: Iterator<Item = (&'a EntryId, &'a cob::Entry)>>(
+        &mut self,
+        entry: &cob::Entry,
+        _concurrent: I,
+        _repo: &R,
+    ) -> Result<(), Self::Error> {
+        self.push(entry)
+    }
+}
@@ -13,9 +13,20 @@
 #![warn(missing_docs)]
 
 pub mod actions;
+pub mod batch;
+pub mod cache;
+pub mod diff;
+pub mod document;
+pub mod estimate;
+pub mod graph;
+pub mod history;
+pub mod milestone;
 pub mod state;
+pub mod template;
+pub mod trailers;
+pub mod verify;
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Deref;
 use std::str::FromStr;
 use std::sync::LazyLock;
@@ -37,7 +48,23 @@ use radicle::prelude::{Did, Doc, ReadRepository, RepoId};
 use radicle::storage::{HasRepoId, RepositoryError, SignRepository, WriteRepository};
 
 pub use actions::Action;
-pub use state::{Plan, PlanStatus, Task, TaskId};
+pub use batch::Batch;
+pub use cache::PlanCache;
+pub use diff::PlanDiff;
+pub use document::{DocumentSync, PlanDocument};
+pub use estimate::Estimate;
+pub use graph::TaskSchedule;
+pub use history::PlanEvent;
+pub use milestone::{DueDate, Milestone, MilestoneId};
+pub use state::{
+    Approval, DependencyError, DependencyErrorKind, Plan, PlanStatus, Task, TaskId, TransitionError,
+};
+pub use template::PlanTemplate;
+pub use trailers::{CommitTrailers, TaskTrailer};
+pub use verify::{CommitReport, CommitStatus};
+
+use cache::{NoCache, Update as _};
+use state::{Clock, Element, Register};
 
 /// Plan operation.
 pub type Op = cob::Op<Action>;
@@ -49,6 +76,57 @@ pub static TYPENAME: LazyLock<TypeName> =
 /// Identifier for a plan.
 pub type PlanId = ObjectId;
 
+/// Get the ID of a task created by a change.
+///
+/// The `index` counts the tasks created by the change, starting from zero. The first
+/// task is identified by the change's entry ID; further tasks created by the same change
+/// get an ID derived from the entry ID and their index, so that every task is distinct.
+pub fn task_id(entry: EntryId, index: usize) -> TaskId {
+    if index == 0 {
+        return entry;
+    }
+    let data = format!("{entry}/task/{index}");
+
+    radicle::git::raw::Oid::hash_object(radicle::git::raw::ObjectType::Blob, data.as_bytes())
+        .map(TaskId::from)
+        .expect("task_id: hashing in memory cannot fail")
+}
+
+/// Get the ID of a milestone created by a change.
+///
+/// The `index` counts the milestones created by the change, starting from zero. The ID
+/// is derived from the change's entry ID and the index, so that it is distinct from the
+/// IDs of tasks created by the same change.
+pub fn milestone_id(entry: EntryId, index: usize) -> MilestoneId {
+    let data = format!("{entry}/milestone/{index}");
+
+    radicle::git::raw::Oid::hash_object(radicle::git::raw::ObjectType::Blob, data.as_bytes())
+        .map(MilestoneId::from)
+        .expect("milestone_id: hashing in memory cannot fail")
+}
+
+/// Counts the tasks and milestones created by the actions of a change, to identify
+/// new ones. See [`task_id`] and [`milestone_id`].
+#[derive(Debug, Default)]
+pub(crate) struct Created {
+    tasks: usize,
+    milestones: usize,
+}
+
+impl Created {
+    /// Get the index of an action among the earlier actions of the same kind, and
+    /// count it.
+    pub(crate) fn index(&mut self, action: &Action) -> usize {
+        let count = match action {
+            Action::AddTask { .. } => &mut self.tasks,
+            Action::AddMilestone { .. } => &mut self.milestones,
+            _ => return 0,
+        };
+        *count += 1;
+        *count - 1
+    }
+}
+
 /// Error updating or creating plans.
 #[derive(Error, Debug)]
 pub enum Error {
@@ -76,6 +154,9 @@ pub enum Error {
     /// The identity doc is missing.
     #[error("identity document missing")]
     MissingIdentity,
+    /// Error listing the plans of a repository.
+    #[error("listing plans: {0}")]
+    List(Box<dyn std::error::Error + Send + Sync + 'static>),
     /// General error initializing a plan.
     #[error("initialization failed: {0}")]
     Init(&'static str),
@@ -88,6 +169,31 @@ pub enum Error {
     /// Invalid task index.
     #[error("invalid task index: {0}")]
     InvalidTaskIndex(usize),
+    /// Repository error.
+    #[error("repository: {0}")]
+    Repository(#[from] RepositoryError),
+    /// Plan cache error.
+    #[error("cache: {0}")]
+    Cache(#[from] cache::Error),
+    /// Plan document error.
+    #[error("document: {0}")]
+    Document(#[from] document::DocumentError),
+    /// Git error.
+    #[error("git: {0}")]
+    Git(#[from] radicle::git::raw::Error),
+    /// A batch without actions was committed.
+    #[error("batch has no actions")]
+    EmptyBatch,
+    /// Status change rejected by the plan status state machine.
+    #[error("cannot change plan status from {from} to {to}: {reason}")]
+    InvalidTransition {
+        /// Status of the plan.
+        from: PlanStatus,
+        /// Requested status.
+        to: PlanStatus,
+        /// Why the change was rejected.
+        reason: TransitionError,
+    },
 }
 
 impl cob::store::CobWithType for Plan {
@@ -120,10 +226,15 @@ impl store::Cob for Plan {
         let thread = Thread::new(op.id, comment);
         let mut plan = Plan::new(title, description, thread, op.author.into(), op.timestamp);
 
+        let mut created = Created::default();
         for action in actions {
+            let index = created.index(&action);
             match plan.authorization(&action, &op.author, &doc)? {
+                Authorization::Allow if plan.lacks_approval(&action, &doc) => {
+                    log::warn!(target: "plan", "Ignoring approval of {} without enough delegate approvals", op.id);
+                }
                 Authorization::Allow => {
-                    plan.apply_action(action, op.id, op.author, op.timestamp)?;
+                    plan.apply_action(action, op.id, index, op.author, op.timestamp, &BTreeSet::new())?;
                 }
                 Authorization::Deny => {
                     return Err(Error::NotAuthorized(op.author, action));
@@ -141,19 +252,77 @@ impl store::Cob for Plan {
         op: Op,
         concurrent: I,
         repo: &R,
+    ) -> Result<(), Error> {
+        let concurrent = concurrent
+            .into_iter()
+            .map(|entry| *entry.id())
+            .collect::<BTreeSet<_>>();
+
+        self.apply_op(op, &concurrent, repo)
+    }
+}
+
+impl<R: ReadRepository> cob::Evaluate<R> for Plan {
+    type Error = Error;
+
+    fn init(entry: &cob::Entry, repo: &R) -> Result<Self, Self::Error> {
+        let op = Op::try_from(entry)?;
+        let object = Plan::from_root(op, repo)?;
+        Ok(object)
+    }
+
+    fn apply<'a, I: Iterator<Item = (&'a EntryId, &'a cob::Entry)>>(
+        &mut self,
+        entry: &cob::Entry,
+        concurrent: I,
+        repo: &R,
+    ) -> Result<(), Self::Error> {
+        let op = Op::try_from(entry)?;
+        self.op(op, concurrent.map(|(_, e)| e), repo)
+    }
+}
+
+impl Plan {
+    /// Apply a change, given the IDs of the changes concurrent with it.
+    pub(crate) fn apply_op<R: ReadRepository>(
+        &mut self,
+        op: Op,
+        concurrent: &BTreeSet<EntryId>,
+        repo: &R,
     ) -> Result<(), Error> {
         let doc = op.identity_doc(repo)?.ok_or(Error::MissingIdentity)?;
-        let _concurrent = concurrent.into_iter().collect::<Vec<_>>();
 
+        let mut created = Created::default();
         for action in op.actions {
             log::trace!(target: "plan", "Applying {} {action:?}", op.id);
 
+            let index = created.index(&action);
             match self.authorization(&action, &op.author, &doc)? {
+                Authorization::Allow if self.lacks_approval(&action, &doc) => {
+                    log::warn!(target: "plan", "Ignoring approval of {} without enough delegate approvals", op.id);
+                }
                 Authorization::Allow => {
-                    if let Err(e) = self.apply_action(action.clone(), op.id, op.author, op.timestamp) {
+                    if let Err(e) = self.apply_action(
+                        action.clone(),
+                        op.id,
+                        index,
+                        op.author,
+                        op.timestamp,
+                        concurrent,
+                    ) {
                         log::error!(target: "plan", "Error applying {}: {e}", op.id);
                         return Err(e);
                     }
+                    // A draft is approved once enough delegates approved it.
+                    if matches!(action, Action::Approve { .. })
+                        && self.status == PlanStatus::Draft
+                        && self.is_approved(&doc)
+                    {
+                        let clock = Clock::new(op.timestamp, op.id);
+                        if self.write(Register::Status, clock, concurrent) {
+                            self.status = PlanStatus::Approved;
+                        }
+                    }
                 }
                 Authorization::Deny => {
                     return Err(Error::NotAuthorized(op.author, action));
@@ -165,54 +334,125 @@ impl store::Cob for Plan {
         }
         Ok(())
     }
-}
 
-impl<R: ReadRepository> cob::Evaluate<R> for Plan {
-    type Error = Error;
+    /// Whether the action approves a draft that doesn't have enough delegate approvals.
+    ///
+    /// A draft only becomes approved once delegates meeting the identity document's
+    /// threshold approved it, so a `status` action can't approve it on its own.
+    fn lacks_approval(&self, action: &Action, doc: &Doc) -> bool {
+        matches!(action, Action::SetStatus { status: PlanStatus::Approved, .. })
+            && self.status == PlanStatus::Draft
+            && !self.is_approved(doc)
+    }
 
-    fn init(entry: &cob::Entry, repo: &R) -> Result<Self, Self::Error> {
-        let op = Op::try_from(entry)?;
-        let object = Plan::from_root(op, repo)?;
-        Ok(object)
+    /// Record a write to a register, returning whether the write takes effect.
+    ///
+    /// A write always wins over the writes it causally follows: its [`Clock`] is
+    /// advanced past theirs. Among concurrent writes, the one with the greatest clock
+    /// wins, so that all peers converge regardless of the order in which changes are
+    /// applied.
+    fn write(&mut self, register: Register, clock: Clock, concurrent: &BTreeSet<EntryId>) -> bool {
+        let writes = self.clocks.entry(register).or_default();
+        let clock = match writes.iter().rev().find(|c| !concurrent.contains(&c.entry)) {
+            Some(observed) => clock.after(*observed),
+            None => clock,
+        };
+        let wins = writes.last().is_none_or(|winner| clock > *winner);
+        writes.insert(clock);
+
+        wins
     }
 
-    fn apply<'a, I: Iterator<Item = (&'a EntryId, &'a cob::Entry)>>(
-        &mut self,
-        entry: &cob::Entry,
-        concurrent: I,
-        repo: &R,
-    ) -> Result<(), Self::Error> {
-        let op = Op::try_from(entry)?;
-        self.op(op, concurrent.map(|(_, e)| e), repo)
+    /// Tag a set element as added by the given change.
+    fn tag(&mut self, element: Element, entry: EntryId) {
+        self.tags.entry(element).or_default().insert(entry);
+    }
+
+    /// Remove the tags of a set element that were observed by the removing change.
+    ///
+    /// Additions made by concurrent changes survive the removal, giving the set
+    /// add-wins semantics. Returns whether the element should be removed.
+    fn untag(&mut self, element: Element, concurrent: &BTreeSet<EntryId>) -> bool {
+        let Some(tags) = self.tags.get_mut(&element) else {
+            return true;
+        };
+        tags.retain(|e| concurrent.contains(e));
+
+        if tags.is_empty() {
+            self.tags.remove(&element);
+            true
+        } else {
+            false
+        }
     }
-}
 
-impl Plan {
     /// Apply a single action to the plan.
+    ///
+    /// The `index` is the number of tasks or milestones created by earlier actions in the
+    /// same change, and is used to identify a new task or milestone. The `concurrent` set
+    /// contains the IDs of changes that are concurrent with the one being applied, and is
+    /// used to resolve conflicting writes.
     fn apply_action(
         &mut self,
         action: Action,
         entry: EntryId,
+        index: usize,
         author: ActorId,
         timestamp: Timestamp,
+        concurrent: &BTreeSet<EntryId>,
     ) -> Result<(), Error> {
+        let clock = Clock::new(timestamp, entry);
+
+        if action.is_revision() && self.write(Register::Revision, clock, concurrent) {
+            self.revision = Some(entry);
+        }
         match action {
             Action::Open { title, description, .. } => {
-                self.title = title;
-                self.description = description;
+                if self.write(Register::Title, clock, concurrent) {
+                    self.title = title;
+                }
+                if self.write(Register::Description, clock, concurrent) {
+                    self.description = description;
+                }
             }
             Action::EditTitle { title } => {
-                self.title = title.to_string();
+                if self.write(Register::Title, clock, concurrent) {
+                    self.title = title.to_string();
+                }
             }
             Action::EditDescription { description, .. } => {
-                self.description = description;
+                if self.write(Register::Description, clock, concurrent) {
+                    self.description = description;
+                }
             }
-            Action::SetStatus { status } => {
-                self.status = status;
+            Action::SetStatus { status, .. } => {
+                // A change concurrent with the latest status change didn't see the
+                // current status, so it isn't checked against it: the two are
+                // resolved last-writer-wins instead. Whether all tasks are done is
+                // only checked when the change is made, since tasks may have changed
+                // concurrently; see `PlanMut::transition`.
+                let observed = self
+                    .clocks
+                    .get(&Register::Status)
+                    .and_then(BTreeSet::last)
+                    .is_none_or(|prev| !concurrent.contains(&prev.entry));
+                // A disallowed transition is ignored rather than rejected: failing would
+                // drop the whole change, and every change built on it.
+                if observed && !self.status.can_transition_to(status) {
+                    log::warn!(
+                        target: "plan",
+                        "Ignoring status change of {entry} from {} to {status}: not allowed",
+                        self.status
+                    );
+                    return Ok(());
+                }
+                if self.write(Register::Status, clock, concurrent) {
+                    self.status = status;
+                }
             }
             Action::AddTask { subject, description, estimate, affected_files } => {
                 let task = Task::new(
-                    entry,
+                    task_id(entry, index),
                     subject,
                     description,
                     estimate,
@@ -223,6 +463,18 @@ impl Plan {
                 self.tasks.push(task);
             }
             Action::EditTask { task_id, subject, description, estimate, affected_files } => {
+                if self.task(&task_id).is_none() {
+                    return Ok(());
+                }
+                let subject = subject
+                    .filter(|_| self.write(Register::TaskSubject(task_id), clock, concurrent));
+                let description = description
+                    .filter(|_| self.write(Register::TaskDescription(task_id), clock, concurrent));
+                let estimate = estimate
+                    .filter(|_| self.write(Register::TaskEstimate(task_id), clock, concurrent));
+                let affected_files = affected_files
+                    .filter(|_| self.write(Register::TaskAffectedFiles(task_id), clock, concurrent));
+
                 if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
                     if let Some(s) = subject {
                         task.subject = s;
@@ -243,14 +495,31 @@ impl Plan {
                 log::debug!(target: "plan", "Ignoring legacy SetTaskStatus action");
             }
             Action::LinkTaskToCommit { task_id, commit } => {
+                if self.task(&task_id).is_none()
+                    || !self.write(Register::TaskCommit(task_id), clock, concurrent)
+                {
+                    return Ok(());
+                }
                 if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
                     task.linked_commit = Some(commit);
                 }
             }
             Action::RemoveTask { task_id } => {
+                // Subtasks of a removed task move up to its parent.
+                let parent = self.task(&task_id).and_then(|t| t.parent);
                 self.tasks.retain(|t| t.id != task_id);
+                for task in &mut self.tasks {
+                    task.blocked_by.retain(|b| b != &task_id);
+                    if task.parent == Some(task_id) {
+                        task.parent = parent;
+                    }
+                }
+                self.dependency_errors.retain(|e| e.task != task_id);
             }
             Action::ReorderTasks { task_ids } => {
+                if !self.write(Register::TaskOrder, clock, concurrent) {
+                    return Ok(());
+                }
                 let mut reordered = Vec::new();
                 for id in task_ids {
                     if let Some(task) = self.tasks.iter().find(|t| t.id == id).cloned() {
@@ -266,32 +535,171 @@ impl Plan {
                 self.tasks = reordered;
             }
             Action::SetTaskBlockedBy { task_id, blocked_by } => {
+                if self.task(&task_id).is_none()
+                    || !self.write(Register::TaskBlockedBy(task_id), clock, concurrent)
+                {
+                    return Ok(());
+                }
+                let (blocked_by, errors) = self.validate_blockers(&task_id, blocked_by);
+                for error in &errors {
+                    log::warn!(target: "plan", "Dropping dependency in {entry}: {error}");
+                }
+                self.dependency_errors.retain(|e| e.task != task_id);
+                self.dependency_errors.extend(errors);
+
                 if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
                     task.blocked_by = blocked_by;
                 }
             }
+            Action::SetTaskParent { task_id, parent } => {
+                if self.task(&task_id).is_none() {
+                    return Ok(());
+                }
+                // A parent must be another existing task, and not one of the task's own
+                // subtasks, so that parents never form a cycle.
+                if let Some(p) = parent {
+                    if p == task_id || self.task(&p).is_none() || self.is_ancestor(&task_id, &p) {
+                        log::warn!(target: "plan", "Ignoring invalid parent {p} of task {task_id} in {entry}");
+                        return Ok(());
+                    }
+                }
+                if !self.write(Register::TaskParent(task_id), clock, concurrent) {
+                    return Ok(());
+                }
+                if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+                    task.parent = parent;
+                }
+            }
+            Action::SetTaskMilestone { task_id, milestone_id } => {
+                if self.task(&task_id).is_none() {
+                    return Ok(());
+                }
+                if let Some(m) = milestone_id {
+                    if self.milestone(&m).is_none() {
+                        log::warn!(target: "plan", "Ignoring missing milestone {m} of task {task_id} in {entry}");
+                        return Ok(());
+                    }
+                }
+                if !self.write(Register::TaskMilestone(task_id), clock, concurrent) {
+                    return Ok(());
+                }
+                if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+                    task.milestone = milestone_id;
+                }
+            }
+            Action::AddMilestone { name, due } => {
+                let milestone = Milestone::new(milestone_id(entry, index), name, due, author, timestamp);
+                self.milestones.push(milestone);
+            }
+            Action::EditMilestone { milestone_id, name, due } => {
+                if self.milestone(&milestone_id).is_none() {
+                    return Ok(());
+                }
+                let name = name
+                    .filter(|_| self.write(Register::MilestoneName(milestone_id), clock, concurrent));
+                let due = due
+                    .filter(|_| self.write(Register::MilestoneDue(milestone_id), clock, concurrent));
+
+                if let Some(milestone) = self.milestones.iter_mut().find(|m| m.id == milestone_id) {
+                    if let Some(n) = name {
+                        milestone.name = n;
+                    }
+                    if let Some(d) = due {
+                        milestone.due = d;
+                    }
+                }
+            }
+            Action::RemoveMilestone { milestone_id } => {
+                // Tasks of a removed milestone are kept, without a milestone.
+                self.milestones.retain(|m| m.id != milestone_id);
+                for task in &mut self.tasks {
+                    if task.milestone == Some(milestone_id) {
+                        task.milestone = None;
+                    }
+                }
+            }
+            Action::ReorderMilestones { milestone_ids } => {
+                if !self.write(Register::MilestoneOrder, clock, concurrent) {
+                    return Ok(());
+                }
+                let mut reordered = Vec::new();
+                for id in milestone_ids {
+                    if let Some(milestone) = self.milestones.iter().find(|m| m.id == id).cloned() {
+                        reordered.push(milestone);
+                    }
+                }
+                // Keep any milestones not in the reorder list at the end
+                for milestone in &self.milestones {
+                    if !reordered.iter().any(|m| m.id == milestone.id) {
+                        reordered.push(milestone.clone());
+                    }
+                }
+                self.milestones = reordered;
+            }
             Action::LinkIssue { issue_id } => {
+                self.tag(Element::Issue(issue_id), entry);
                 self.related_issues.insert(issue_id);
             }
             Action::UnlinkIssue { issue_id } => {
-                self.related_issues.remove(&issue_id);
+                if self.untag(Element::Issue(issue_id), concurrent) {
+                    self.related_issues.remove(&issue_id);
+                }
             }
             Action::LinkPatch { patch_id } => {
+                self.tag(Element::Patch(patch_id), entry);
                 self.related_patches.insert(patch_id);
             }
             Action::UnlinkPatch { patch_id } => {
-                self.related_patches.remove(&patch_id);
+                if self.untag(Element::Patch(patch_id), concurrent) {
+                    self.related_patches.remove(&patch_id);
+                }
             }
             Action::LinkTaskToIssue { task_id, issue_id } => {
+                if self.task(&task_id).is_none()
+                    || !self.write(Register::TaskIssue(task_id), clock, concurrent)
+                {
+                    return Ok(());
+                }
                 if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
                     task.linked_issue = Some(issue_id);
                 }
             }
+            Action::LinkTaskToPatch { task_id, patch_id } => {
+                if self.task(&task_id).is_none()
+                    || !self.write(Register::TaskPatch(task_id), clock, concurrent)
+                {
+                    return Ok(());
+                }
+                if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+                    task.linked_patch = Some(patch_id);
+                }
+            }
+            Action::AssignTask { task_id, add, remove } => {
+                if self.task(&task_id).is_none() {
+                    return Ok(());
+                }
+                let removed = remove
+                    .into_iter()
+                    .filter(|did| self.untag(Element::TaskAssignee(task_id, *did), concurrent))
+                    .collect::<Vec<_>>();
+                for did in &add {
+                    self.tag(Element::TaskAssignee(task_id, *did), entry);
+                }
+                if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+                    for did in removed {
+                        task.assignees.remove(&did);
+                    }
+                    task.assignees.extend(add);
+                }
+            }
             Action::AddCriticalFile { path } => {
+                self.tag(Element::CriticalFile(path.clone()), entry);
                 self.critical_files.insert(path);
             }
             Action::RemoveCriticalFile { path } => {
-                self.critical_files.remove(&path);
+                if self.untag(Element::CriticalFile(path.clone()), concurrent) {
+                    self.critical_files.remove(&path);
+                }
             }
             Action::Comment { body, reply_to, embeds } => {
                 thread::comment(
@@ -312,10 +720,24 @@ impl Plan {
                 thread::redact(&mut self.thread, entry, id)?;
             }
             Action::Label { labels } => {
-                self.labels = BTreeSet::from_iter(labels);
+                if self.write(Register::Labels, clock, concurrent) {
+                    self.labels = BTreeSet::from_iter(labels);
+                }
             }
             Action::Assign { assignees } => {
-                self.assignees = BTreeSet::from_iter(assignees);
+                if self.write(Register::Assignees, clock, concurrent) {
+                    self.assignees = BTreeSet::from_iter(assignees);
+                }
+            }
+            Action::Approve { revision } => {
+                if self.write(Register::Approval(author), clock, concurrent) {
+                    self.approvals.insert(author.into(), Approval { revision, timestamp });
+                }
+            }
+            Action::Unapprove => {
+                if self.write(Register::Approval(author), clock, concurrent) {
+                    self.approvals.remove(&author.into());
+                }
             }
         }
         Ok(())
@@ -334,6 +756,10 @@ impl Plan {
         }
         let author: ActorId = *self.author.id().as_key();
         let outcome = match action {
+            // Only delegates can approve plans, or restore archived ones.
+            Action::SetStatus { status, .. } if self.status.requires_delegate(*status) => {
+                Authorization::Deny
+            }
             // Plan authors can edit their own plans.
             Action::Open { .. }
             | Action::EditTitle { .. }
@@ -345,14 +771,27 @@ impl Plan {
             | Action::RemoveTask { .. }
             | Action::ReorderTasks { .. }
             | Action::SetTaskBlockedBy { .. }
+            | Action::SetTaskParent { .. }
+            | Action::SetTaskMilestone { .. }
+            | Action::AddMilestone { .. }
+            | Action::EditMilestone { .. }
+            | Action::RemoveMilestone { .. }
+            | Action::ReorderMilestones { .. }
             | Action::LinkIssue { .. }
             | Action::UnlinkIssue { .. }
             | Action::LinkPatch { .. }
             | Action::UnlinkPatch { .. }
             | Action::LinkTaskToIssue { .. }
             | Action::LinkTaskToCommit { .. }
+            | Action::LinkTaskToPatch { .. }
             | Action::AddCriticalFile { .. }
             | Action::RemoveCriticalFile { .. } => Authorization::from(*actor == author),
+            // Plan authors can assign anyone to tasks, and others can assign or unassign
+            // themselves.
+            Action::AssignTask { add, remove, .. } => {
+                let did = Did::from(*actor);
+                Authorization::from(*actor == author || add.iter().chain(remove).all(|d| *d == did))
+            }
             // Only delegates can assign or label.
             Action::Assign { assignees } => {
                 if assignees == &self.assignees {
@@ -368,6 +807,8 @@ impl Plan {
                     Authorization::Deny
                 }
             }
+            // Only delegates can approve.
+            Action::Approve { .. } | Action::Unapprove => Authorization::Deny,
             // All roles can comment.
             Action::Comment { .. } => Authorization::Allow,
             // Authors can edit/redact their own comments.
@@ -465,17 +906,49 @@ where
         embeds: Vec<Embed<Uri>>,
         signer: &Device<G>,
     ) -> Result<(ObjectId, Plan), Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.create_with(title, description, embeds, vec![], signer)
+    }
+
+    /// Create a new plan with an initial set of actions, such as labels, assignees,
+    /// tasks and links, all committed in the plan's root change.
+    pub fn create_with<G>(
+        &mut self,
+        title: String,
+        description: String,
+        embeds: Vec<Embed<Uri>>,
+        actions: Vec<Action>,
+        signer: &Device<G>,
+    ) -> Result<(ObjectId, Plan), Error>
     where
         G: crypto::signature::Signer<crypto::Signature>,
     {
         use nonempty::NonEmpty;
 
+        // Labels and assignees are restricted to delegates. Reject them up-front, as a
+        // root change containing them would otherwise fail to evaluate.
+        let actor = signer.public_key();
+        let doc = self.raw.as_ref().identity_doc()?;
+        if !doc.is_delegate(&actor.into()) {
+            let restricted = actions.iter().find(|a| match a {
+                Action::Label { labels } => !labels.is_empty(),
+                Action::Assign { assignees } => !assignees.is_empty(),
+                _ => false,
+            });
+            if let Some(action) = restricted {
+                return Err(Error::NotAuthorized(*actor, action.clone()));
+            }
+        }
+
         let action = Action::Open {
             title,
             description,
             embeds: embeds.clone(),
         };
-        let actions = NonEmpty::new(action);
+        let mut actions = NonEmpty::from((action, actions));
+        actions.tail.retain(|a| !matches!(a, Action::Open { .. }));
 
         self.raw.create("Create plan", actions, embeds, signer).map_err(Error::from)
     }
@@ -507,6 +980,64 @@ where
             });
         Ok(counts)
     }
+
+    /// Get the history of a plan: every recorded action, in the order its changes are
+    /// applied, attributed to the change author.
+    pub fn history(&self, id: &ObjectId) -> Result<Vec<PlanEvent>, Error> {
+        let history = cob::get::<history::History, _>(self.raw.as_ref(), &TYPENAME, id)
+            .map_err(store::Error::from)?
+            .ok_or_else(|| store::Error::NotFound(TYPENAME.clone(), *id))?;
+
+        Ok(history.object.into_events())
+    }
+
+    /// Get a plan as of a past change: the state obtained by evaluating that change
+    /// and its ancestors. Returns `None` if the plan doesn't exist or the change isn't
+    /// part of it.
+    pub fn get_at(&self, id: &ObjectId, entry: &EntryId) -> Result<Option<Plan>, Error> {
+        let Some(history) = cob::get::<history::History, _>(self.raw.as_ref(), &TYPENAME, id)
+            .map_err(store::Error::from)?
+        else {
+            return Ok(None);
+        };
+        history.object.evaluate_at(entry, self.raw.as_ref())
+    }
+
+    /// Find the plans that reference an issue, directly or through one of their tasks.
+    pub fn find_by_issue(&self, id: &ObjectId) -> Result<Vec<(PlanId, Plan)>, Error> {
+        self.find(|p| p.references_issue(id))
+    }
+
+    /// Find the plans that reference a patch.
+    pub fn find_by_patch(&self, id: &ObjectId) -> Result<Vec<(PlanId, Plan)>, Error> {
+        self.find(|p| p.references_patch(id))
+    }
+
+    /// Find the plans with a task linked to a commit.
+    pub fn find_by_commit(&self, oid: &radicle::git::Oid) -> Result<Vec<(PlanId, Plan)>, Error> {
+        self.find(|p| p.references_commit(oid))
+    }
+
+    /// Find the plans that reference a file, as a critical file or a file affected by
+    /// one of their tasks.
+    pub fn find_by_file(&self, path: &str) -> Result<Vec<(PlanId, Plan)>, Error> {
+        self.find(|p| p.references_file(path))
+    }
+
+    /// Find the plans with a task assigned to a DID.
+    pub fn find_by_assignee(&self, did: &Did) -> Result<Vec<(PlanId, Plan)>, Error> {
+        self.find(|p| p.tasks_assigned_to(did).next().is_some())
+    }
+
+    /// Find the plans matching a predicate. Plans that fail to load are skipped.
+    fn find(&self, predicate: impl Fn(&Plan) -> bool) -> Result<Vec<(PlanId, Plan)>, Error> {
+        let plans = self
+            .all()?
+            .filter_map(|s| s.ok())
+            .filter(|(_, p)| predicate(p))
+            .collect();
+        Ok(plans)
+    }
 }
 
 impl<'a, R> Plans<'a, R>
@@ -524,18 +1055,207 @@ where
             id: *id,
             plan,
             store: self,
+            cache: NoCache,
+        })
+    }
+}
+
+/// A plans store that writes through to a [`PlanCache`].
+///
+/// Derefs to [`Plans`] for reading from the repository; list queries that should avoid
+/// re-evaluating every plan go through [`PlansMut::cache`].
+pub struct PlansMut<'a, R> {
+    store: Plans<'a, R>,
+    cache: PlanCache,
+}
+
+impl<'a, R> Deref for PlansMut<'a, R> {
+    type Target = Plans<'a, R>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.store
+    }
+}
+
+impl<'a, R> PlansMut<'a, R> {
+    /// Create a plans store backed by the given cache.
+    pub fn new(store: Plans<'a, R>, cache: PlanCache) -> Self {
+        Self { store, cache }
+    }
+
+    /// Get the plan cache.
+    pub fn cache(&self) -> &PlanCache {
+        &self.cache
+    }
+}
+
+impl<'a, R> PlansMut<'a, R>
+where
+    R: WriteRepository + SignRepository + cob::Store<Namespace = NodeId>,
+{
+    /// Create a new plan, and add it to the cache.
+    pub fn create<G>(
+        &mut self,
+        title: String,
+        description: String,
+        embeds: Vec<Embed<Uri>>,
+        signer: &Device<G>,
+    ) -> Result<(ObjectId, Plan), Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.create_with(title, description, embeds, vec![], signer)
+    }
+
+    /// Create a new plan with an initial set of actions, and add it to the cache.
+    pub fn create_with<G>(
+        &mut self,
+        title: String,
+        description: String,
+        embeds: Vec<Embed<Uri>>,
+        actions: Vec<Action>,
+        signer: &Device<G>,
+    ) -> Result<(ObjectId, Plan), Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        let (id, plan) = self.store.create_with(title, description, embeds, actions, signer)?;
+        self.cache.update(&self.store.rid(), &id, &plan)?;
+
+        Ok((id, plan))
+    }
+
+    /// Get a plan for mutation. Changes are written through to the cache.
+    pub fn get_mut<'g>(
+        &'g mut self,
+        id: &ObjectId,
+    ) -> Result<PlanMut<'a, 'g, R, &'g mut PlanCache>, store::Error> {
+        let plan = self
+            .store
+            .raw
+            .get(id)?
+            .ok_or_else(move || store::Error::NotFound(TYPENAME.clone(), *id))?;
+
+        Ok(PlanMut {
+            id: *id,
+            plan,
+            store: &mut self.store,
+            cache: &mut self.cache,
         })
     }
+
+    /// Re-evaluate a plan from the repository and write it to the cache.
+    pub fn write(&mut self, id: &ObjectId) -> Result<(), Error> {
+        let heads = self.heads()?.remove(id);
+        self.write_at(id, heads.as_deref())
+    }
+
+    /// Rebuild the cache for this repository, returning the number of plans cached.
+    ///
+    /// Plans that fail to evaluate are logged and skipped.
+    pub fn write_all(&mut self) -> Result<usize, Error> {
+        let rid = self.store.rid();
+        let heads = self.heads()?;
+        let mut count = 0;
+
+        self.cache.remove_all(&rid)?;
+        for result in self.store.all()? {
+            match result {
+                Ok((id, plan)) => {
+                    self.cache.update(&rid, &id, &plan)?;
+                    if let Some(heads) = heads.get(&id) {
+                        self.cache.set_heads(&rid, &id, heads)?;
+                    }
+                    count += 1;
+                }
+                Err(e) => {
+                    log::warn!(target: "plan", "Skipping plan that failed to load: {e}");
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Bring the cache up to date with the repository, returning the number of plans
+    /// that were re-evaluated or removed.
+    ///
+    /// Plans whose refs changed since they were cached, eg. because changes were
+    /// fetched from other peers, are re-evaluated; plans that are no longer in the
+    /// repository are removed. Plans that fail to evaluate are logged and skipped.
+    pub fn refresh(&mut self) -> Result<usize, Error> {
+        let rid = self.store.rid();
+        let cached = self.cache.heads(&rid)?;
+        let current = self.heads()?;
+        let mut count = 0;
+
+        for id in cached.keys().filter(|id| !current.contains_key(id)) {
+            self.cache.remove(&rid, id)?;
+            count += 1;
+        }
+        for (id, heads) in &current {
+            if cached.get(id).is_some_and(|h| h.as_ref() == Some(heads)) {
+                continue;
+            }
+            match self.write_at(id, Some(heads)) {
+                Ok(()) => count += 1,
+                Err(Error::Cache(e)) => return Err(e.into()),
+                Err(e) => {
+                    log::warn!(target: "plan", "Skipping plan {id} that failed to load: {e}");
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Re-evaluate a plan and write it to the cache, along with the ref heads it was
+    /// evaluated from.
+    fn write_at(&mut self, id: &ObjectId, heads: Option<&str>) -> Result<(), Error> {
+        let rid = self.store.rid();
+        match self.store.get(id)? {
+            Some(plan) => {
+                self.cache.update(&rid, id, &plan)?;
+                if let Some(heads) = heads {
+                    self.cache.set_heads(&rid, id, heads)?;
+                }
+            }
+            None => self.cache.remove(&rid, id)?,
+        }
+        Ok(())
+    }
+
+    /// Get the heads of each plan's refs, across all peers, as a single string that
+    /// changes whenever any of them moves.
+    fn heads(&self) -> Result<BTreeMap<PlanId, String>, Error> {
+        let objects = self
+            .store
+            .raw
+            .as_ref()
+            .types(&TYPENAME)
+            .map_err(|e| Error::List(Box::new(e)))?;
+
+        Ok(objects
+            .into_iter()
+            .map(|(id, refs)| {
+                let mut heads = refs.iter().map(|r| r.target.id.to_string()).collect::<Vec<_>>();
+                heads.sort();
+                (id, heads.join(","))
+            })
+            .collect())
+    }
 }
 
 /// A mutable plan handle for performing updates.
-pub struct PlanMut<'a, 'g, R> {
+///
+/// If the handle was obtained through [`PlansMut`], every change is also written
+/// to the plan cache.
+pub struct PlanMut<'a, 'g, R, C = NoCache> {
     id: ObjectId,
     plan: Plan,
     store: &'g mut Plans<'a, R>,
+    cache: C,
 }
 
-impl<R> std::fmt::Debug for PlanMut<'_, '_, R> {
+impl<R, C> std::fmt::Debug for PlanMut<'_, '_, R, C> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         f.debug_struct("PlanMut")
             .field("id", &self.id)
@@ -544,7 +1264,7 @@ impl<R> std::fmt::Debug for PlanMut<'_, '_, R> {
     }
 }
 
-impl<R> std::ops::Deref for PlanMut<'_, '_, R> {
+impl<R, C> std::ops::Deref for PlanMut<'_, '_, R, C> {
     type Target = Plan;
 
     fn deref(&self) -> &Self::Target {
@@ -552,9 +1272,10 @@ impl<R> std::ops::Deref for PlanMut<'_, '_, R> {
     }
 }
 
-impl<'a, 'g, R> PlanMut<'a, 'g, R>
+impl<'a, 'g, R, C> PlanMut<'a, 'g, R, C>
 where
     R: WriteRepository + SignRepository + cob::Store<Namespace = NodeId>,
+    C: cache::Update,
 {
     /// Get the plan ID.
     pub fn id(&self) -> &ObjectId {
@@ -576,21 +1297,170 @@ where
         operations(&mut tx)?;
 
         let (plan, commit) = tx.commit(message, self.id, &mut self.store.raw, signer)?;
+        self.cache.update(&self.store.rid(), &self.id, &plan)?;
         self.plan = plan;
 
         Ok(commit)
     }
 
-    /// Set the plan status.
+    /// Record the actions added to a [`Batch`] in a single change.
+    ///
+    /// Returns the change's entry ID, and the IDs of the tasks it added, in the order
+    /// their actions were added. See [`task_id`]. Fails with [`Error::EmptyBatch`] if no
+    /// actions were added.
+    pub fn batch<G, F>(
+        &mut self,
+        message: &str,
+        signer: &Device<G>,
+        build: F,
+    ) -> Result<(EntryId, Vec<TaskId>), Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+        F: FnOnce(&mut Batch),
+    {
+        let mut batch = Batch::default();
+        build(&mut batch);
+
+        if batch.is_empty() {
+            return Err(Error::EmptyBatch);
+        }
+        let actions = batch.clone();
+        let entry = self.transaction(message, signer, |tx| {
+            for action in actions {
+                tx.push(action)?;
+            }
+            Ok(())
+        })?;
+
+        Ok((entry, batch.task_ids(entry)))
+    }
+
+    /// Make the plan match a document, returning the changes made. See
+    /// [`PlanDocument::sync`].
+    ///
+    /// The document's edits are applied in a single change. If tasks or milestones need
+    /// reordering, or tasks need new parents or milestones, this is done in a second
+    /// change, as the IDs of added tasks and milestones derive from the first.
+    pub fn apply_document<G>(&mut self, doc: &PlanDocument, signer: &Device<G>) -> Result<Vec<EntryId>, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        let sync = doc.sync(&self.plan)?;
+        let mut changes = Vec::new();
+
+        if !sync.actions.is_empty() {
+            let (entry, _) = self.batch("Apply plan document", signer, |b| {
+                for action in &sync.actions {
+                    b.push(action.clone());
+                }
+            })?;
+            changes.push(entry);
+        }
+        // Without a first change nothing was added, so any entry resolves the references.
+        let entry = changes.first().copied().unwrap_or(*self.plan.root().0);
+        let arrange = sync
+            .reorder(entry)
+            .into_iter()
+            .chain(sync.set_parents(entry))
+            .chain(sync.reorder_milestones(entry))
+            .chain(sync.set_milestones(entry))
+            .collect::<Vec<_>>();
+        if !arrange.is_empty() {
+            let (entry, _) = self.batch("Arrange tasks", signer, |b| {
+                for action in arrange {
+                    b.push(action);
+                }
+            })?;
+            changes.push(entry);
+        }
+        Ok(changes)
+    }
+
+    /// Set the plan status to the status its tasks imply, if the stored status lags
+    /// behind. See [`Plan::effective_status_in`].
+    ///
+    /// Returns the new status, or `None` if the plan was already up to date.
+    pub fn reconcile_status<G>(&mut self, signer: &Device<G>) -> Result<Option<PlanStatus>, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        let status = self.plan.effective_status_in(self.store.raw.as_ref())?;
+        if status == self.plan.status {
+            return Ok(None);
+        }
+        // Tasks done by merging their patch aren't done as far as the plan itself
+        // knows, so completing it is an override.
+        let force = !self.plan.all_tasks_complete();
+        self.transition(status, force, signer)?;
+
+        Ok(Some(status))
+    }
+
+    /// Set the plan status. See [`Plan::check_transition`].
     pub fn set_status<G>(&mut self, status: PlanStatus, signer: &Device<G>) -> Result<EntryId, Error>
     where
         G: crypto::signature::Signer<crypto::Signature>,
     {
+        self.transition(status, false, signer)
+    }
+
+    /// Set the plan status, completing the plan even if not all tasks are done.
+    pub fn force_status<G>(&mut self, status: PlanStatus, signer: &Device<G>) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.transition(status, true, signer)
+    }
+
+    fn transition<G>(&mut self, status: PlanStatus, force: bool, signer: &Device<G>) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.plan.check_transition(status, force).map_err(|reason| Error::InvalidTransition {
+            from: self.plan.status,
+            to: status,
+            reason,
+        })?;
+        // Approving a draft takes enough delegate approvals; the change would be
+        // ignored otherwise.
+        if self.plan.status == PlanStatus::Draft && status == PlanStatus::Approved {
+            let doc = self.store.raw.as_ref().identity_doc()?;
+            if !self.plan.is_approved(&doc) {
+                return Err(Error::InvalidTransition {
+                    from: self.plan.status,
+                    to: status,
+                    reason: TransitionError::NotApproved {
+                        approvals: self.plan.approvers(&doc).count(),
+                        threshold: doc.threshold(),
+                    },
+                });
+            }
+        }
         self.transaction("Set status", signer, |tx| {
-            tx.push(Action::SetStatus { status })
+            tx.push(Action::SetStatus { status, force })
+        })
+    }
+
+    /// Approve the plan's current revision. The plan moves from draft to approved
+    /// once enough delegates approved it. See [`Plan::is_approved`].
+    pub fn approve<G>(&mut self, signer: &Device<G>) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        let revision = self.plan.revision();
+        self.transaction("Approve", signer, |tx| {
+            tx.push(Action::Approve { revision })
         })
     }
 
+    /// Withdraw an approval of the plan.
+    pub fn unapprove<G>(&mut self, signer: &Device<G>) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.transaction("Unapprove", signer, |tx| tx.push(Action::Unapprove))
+    }
+
     /// Edit the plan title.
     pub fn edit_title<G>(&mut self, title: impl ToString, signer: &Device<G>) -> Result<EntryId, Error>
     where
@@ -618,27 +1488,28 @@ where
         })
     }
 
-    /// Add a task to the plan.
+    /// Add a task to the plan, returning its ID.
     pub fn add_task<G>(
         &mut self,
         subject: impl ToString,
         description: Option<String>,
-        estimate: Option<String>,
+        estimate: Option<Estimate>,
         affected_files: Vec<String>,
         signer: &Device<G>,
-    ) -> Result<EntryId, Error>
+    ) -> Result<TaskId, Error>
     where
         G: crypto::signature::Signer<crypto::Signature>,
     {
         let subject = subject.to_string();
-        self.transaction("Add task", signer, |tx| {
+        let entry = self.transaction("Add task", signer, |tx| {
             tx.push(Action::AddTask {
                 subject,
                 description,
                 estimate,
                 affected_files,
             })
-        })
+        })?;
+        Ok(task_id(entry, 0))
     }
 
     /// Link a task to a commit, marking it as done.
@@ -656,13 +1527,45 @@ where
         })
     }
 
+    /// Link tasks to the commits that name them in trailers, walking a revision range
+    /// of the given repository. See [`CommitTrailers::walk`].
+    ///
+    /// The repository is usually the working copy, which may have commits that aren't
+    /// in storage yet; they are linked all the same.
+    ///
+    /// All links are recorded in a single change, and the links made are returned.
+    /// Links the plan already has are skipped; if none are left, no change is made.
+    pub fn sync_commits<G>(
+        &mut self,
+        repo: &radicle::git::raw::Repository,
+        revspec: &str,
+        signer: &Device<G>,
+    ) -> Result<CommitTrailers, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        let mut trailers = CommitTrailers::walk(repo, revspec, &self.id, &self.plan)?;
+        trailers
+            .links
+            .retain(|(task, commit)| self.plan.task(task).is_some_and(|t| t.linked_commit != Some(*commit)));
+
+        if !trailers.links.is_empty() {
+            self.batch("Link tasks to commits", signer, |b| {
+                for (task_id, commit) in &trailers.links {
+                    b.link_task_to_commit(*task_id, *commit);
+                }
+            })?;
+        }
+        Ok(trailers)
+    }
+
     /// Edit a task.
     pub fn edit_task<G>(
         &mut self,
         task_id: TaskId,
         subject: Option<String>,
         description: Option<Option<String>>,
-        estimate: Option<Option<String>>,
+        estimate: Option<Option<Estimate>>,
         affected_files: Option<Vec<String>>,
         signer: &Device<G>,
     ) -> Result<EntryId, Error>
@@ -690,6 +1593,119 @@ where
         })
     }
 
+    /// Reorder the plan's tasks. Tasks not listed keep their relative order, after the listed ones.
+    pub fn reorder_tasks<G>(&mut self, task_ids: Vec<TaskId>, signer: &Device<G>) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.transaction("Reorder tasks", signer, |tx| {
+            tx.push(Action::ReorderTasks { task_ids })
+        })
+    }
+
+    /// Set the tasks that block a task, replacing any existing blockers.
+    pub fn set_task_blocked_by<G>(
+        &mut self,
+        task_id: TaskId,
+        blocked_by: Vec<TaskId>,
+        signer: &Device<G>,
+    ) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.transaction("Set task blockers", signer, |tx| {
+            tx.push(Action::SetTaskBlockedBy { task_id, blocked_by })
+        })
+    }
+
+    /// Make a task a subtask of another, or a top-level task if `parent` is `None`.
+    pub fn set_task_parent<G>(
+        &mut self,
+        task_id: TaskId,
+        parent: Option<TaskId>,
+        signer: &Device<G>,
+    ) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.transaction("Set task parent", signer, |tx| {
+            tx.push(Action::SetTaskParent { task_id, parent })
+        })
+    }
+
+    /// Add a task to a milestone, or remove it from its milestone if `milestone_id` is
+    /// `None`.
+    pub fn set_task_milestone<G>(
+        &mut self,
+        task_id: TaskId,
+        milestone_id: Option<MilestoneId>,
+        signer: &Device<G>,
+    ) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.transaction("Set task milestone", signer, |tx| {
+            tx.push(Action::SetTaskMilestone { task_id, milestone_id })
+        })
+    }
+
+    /// Add a milestone to the plan, returning its ID.
+    pub fn add_milestone<G>(
+        &mut self,
+        name: impl ToString,
+        due: Option<DueDate>,
+        signer: &Device<G>,
+    ) -> Result<MilestoneId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        let name = name.to_string();
+        let entry = self.transaction("Add milestone", signer, |tx| {
+            tx.push(Action::AddMilestone { name, due })
+        })?;
+        Ok(milestone_id(entry, 0))
+    }
+
+    /// Edit a milestone.
+    pub fn edit_milestone<G>(
+        &mut self,
+        milestone_id: MilestoneId,
+        name: Option<String>,
+        due: Option<Option<DueDate>>,
+        signer: &Device<G>,
+    ) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.transaction("Edit milestone", signer, |tx| {
+            tx.push(Action::EditMilestone { milestone_id, name, due })
+        })
+    }
+
+    /// Remove a milestone. Its tasks are kept, without a milestone.
+    pub fn remove_milestone<G>(&mut self, milestone_id: MilestoneId, signer: &Device<G>) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.transaction("Remove milestone", signer, |tx| {
+            tx.push(Action::RemoveMilestone { milestone_id })
+        })
+    }
+
+    /// Reorder the plan's milestones.
+    pub fn reorder_milestones<G>(
+        &mut self,
+        milestone_ids: Vec<MilestoneId>,
+        signer: &Device<G>,
+    ) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.transaction("Reorder milestones", signer, |tx| {
+            tx.push(Action::ReorderMilestones { milestone_ids })
+        })
+    }
+
     /// Link an issue to the plan.
     pub fn link_issue<G>(&mut self, issue_id: ObjectId, signer: &Device<G>) -> Result<EntryId, Error>
     where
@@ -745,6 +1761,64 @@ where
         })
     }
 
+    /// Link a task to the patch that implements it; the task is done once the patch
+    /// is merged.
+    pub fn link_task_to_patch<G>(
+        &mut self,
+        task_id: TaskId,
+        patch_id: ObjectId,
+        signer: &Device<G>,
+    ) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.transaction("Link task to patch", signer, |tx| {
+            tx.push(Action::LinkTaskToPatch { task_id, patch_id })
+        })
+    }
+
+    /// Assign users to a task, and unassign others. Anyone can assign or unassign
+    /// themselves; assigning others requires being the plan author or a delegate.
+    pub fn assign_task<G>(
+        &mut self,
+        task_id: TaskId,
+        add: impl IntoIterator<Item = Did>,
+        remove: impl IntoIterator<Item = Did>,
+        signer: &Device<G>,
+    ) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        let add = add.into_iter().collect::<BTreeSet<_>>();
+        let remove = remove.into_iter().collect::<BTreeSet<_>>();
+
+        self.transaction("Assign task", signer, |tx| {
+            tx.push(Action::AssignTask { task_id, add, remove })
+        })
+    }
+
+    /// Mark a file as critical to the plan.
+    pub fn add_critical_file<G>(&mut self, path: impl ToString, signer: &Device<G>) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        let path = path.to_string();
+        self.transaction("Add critical file", signer, |tx| {
+            tx.push(Action::AddCriticalFile { path })
+        })
+    }
+
+    /// Unmark a critical file.
+    pub fn remove_critical_file<G>(&mut self, path: impl ToString, signer: &Device<G>) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        let path = path.to_string();
+        self.transaction("Remove critical file", signer, |tx| {
+            tx.push(Action::RemoveCriticalFile { path })
+        })
+    }
+
     /// Add a comment to the plan.
     pub fn comment<G, S>(
         &mut self,
@@ -763,6 +1837,34 @@ where
         })
     }
 
+    /// Edit a comment.
+    pub fn edit_comment<G, S>(
+        &mut self,
+        id: CommentId,
+        body: S,
+        embeds: Vec<Embed<Uri>>,
+        signer: &Device<G>,
+    ) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+        S: ToString,
+    {
+        let body = body.to_string();
+        self.transaction("Edit comment", signer, |tx| {
+            tx.push(Action::CommentEdit { id, body, embeds })
+        })
+    }
+
+    /// Redact a comment.
+    pub fn redact_comment<G>(&mut self, id: CommentId, signer: &Device<G>) -> Result<EntryId, Error>
+    where
+        G: crypto::signature::Signer<crypto::Signature>,
+    {
+        self.transaction("Redact comment", signer, |tx| {
+            tx.push(Action::CommentRedact { id })
+        })
+    }
+
     /// Label the plan.
     pub fn label<G>(
         &mut self,
@@ -793,3 +1895,563 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use radicle::git::Oid;
+
+    fn actor() -> ActorId {
+        state::test::author()
+    }
+
+    fn entry(n: u8) -> EntryId {
+        Oid::from_str(&format!("{n:040x}")).unwrap()
+    }
+
+    fn plan() -> Plan {
+        state::test::plan(entry(0))
+    }
+
+    fn apply(plan: &mut Plan, action: Action, n: u8, secs: u64, concurrent: &[u8]) {
+        let concurrent = concurrent.iter().copied().map(entry).collect::<BTreeSet<_>>();
+        plan.apply_action(action, entry(n), 0, actor(), Timestamp::from_secs(secs), &concurrent)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_status_last_writer_wins() {
+        let approved = Action::SetStatus { status: PlanStatus::Approved, force: false };
+        let archived = Action::SetStatus { status: PlanStatus::Archived, force: false };
+
+        // Both replication orders converge on the write with the later timestamp.
+        let mut a = plan();
+        apply(&mut a, approved.clone(), 1, 20, &[]);
+        apply(&mut a, archived.clone(), 2, 10, &[1]);
+
+        let mut b = plan();
+        apply(&mut b, archived, 2, 10, &[]);
+        apply(&mut b, approved, 1, 20, &[2]);
+
+        assert_eq!(a.status(), &PlanStatus::Approved);
+        assert_eq!(b.status(), &PlanStatus::Approved);
+    }
+
+    #[test]
+    fn test_concurrent_writes_with_equal_timestamps_tiebreak_on_entry() {
+        let first = Action::EditDescription { description: "first".to_string(), embeds: vec![] };
+        let second = Action::EditDescription { description: "second".to_string(), embeds: vec![] };
+
+        let mut a = plan();
+        apply(&mut a, first.clone(), 1, 10, &[]);
+        apply(&mut a, second.clone(), 2, 10, &[1]);
+
+        let mut b = plan();
+        apply(&mut b, second, 2, 10, &[]);
+        apply(&mut b, first, 1, 10, &[2]);
+
+        assert_eq!(a.description(), "second");
+        assert_eq!(b.description(), "second");
+    }
+
+    #[test]
+    fn test_causal_write_wins_over_later_timestamp() {
+        let mut p = plan();
+        apply(&mut p, Action::SetStatus { status: PlanStatus::Approved, force: false }, 1, 20, &[]);
+        // Not concurrent: the second change has seen the first, despite clock skew.
+        apply(&mut p, Action::SetStatus { status: PlanStatus::InProgress, force: false }, 2, 10, &[]);
+
+        assert_eq!(p.status(), &PlanStatus::InProgress);
+    }
+
+    #[test]
+    fn test_writes_converge_across_orders() {
+        let describe = |d: &str| Action::EditDescription { description: d.to_string(), embeds: vec![] };
+        let (x, y, z) = (describe("x"), describe("y"), describe("z"));
+
+        // X and Y are concurrent, and Z follows X with an earlier timestamp, so it is
+        // concurrent with Y too. Every order consistent with causality converges.
+        let orders: [&[(&Action, u8, u64, &[u8])]; 3] = [
+            &[(&x, 1, 30, &[]), (&y, 2, 10, &[1]), (&z, 3, 5, &[2])],
+            &[(&x, 1, 30, &[]), (&z, 3, 5, &[]), (&y, 2, 10, &[1, 3])],
+            &[(&y, 2, 10, &[]), (&x, 1, 30, &[2]), (&z, 3, 5, &[2])],
+        ];
+        for order in orders {
+            let mut p = plan();
+            for (action, n, secs, concurrent) in order {
+                apply(&mut p, (*action).clone(), *n, *secs, concurrent);
+            }
+            assert_eq!(p.description(), "z");
+        }
+    }
+
+    #[test]
+    fn test_concurrent_task_edits_resolve_per_field() {
+        let add = Action::AddTask {
+            subject: "Task".to_string(),
+            description: None,
+            estimate: None,
+            affected_files: vec![],
+        };
+        let rename = Action::EditTask {
+            task_id: entry(1),
+            subject: Some("Renamed".to_string()),
+            description: None,
+            estimate: Some(Some(Estimate::lenient("1d"))),
+            affected_files: None,
+        };
+        let describe = Action::EditTask {
+            task_id: entry(1),
+            subject: Some("Stale".to_string()),
+            description: Some(Some("Details".to_string())),
+            estimate: None,
+            affected_files: None,
+        };
+
+        let mut p = plan();
+        apply(&mut p, add, 1, 0, &[]);
+        apply(&mut p, rename, 3, 30, &[]);
+        apply(&mut p, describe, 2, 20, &[3]);
+
+        // Only the conflicting subject is resolved in favour of the later change.
+        let task = p.task(&entry(1)).unwrap();
+        assert_eq!(task.subject, "Renamed");
+        assert_eq!(task.description.as_deref(), Some("Details"));
+        assert_eq!(task.estimate.as_ref().map(Estimate::as_str), Some("1d"));
+    }
+
+    fn add_task(plan: &mut Plan, n: u8) {
+        let action = Action::AddTask {
+            subject: format!("Task {n}"),
+            description: None,
+            estimate: None,
+            affected_files: vec![],
+        };
+        apply(plan, action, n, u64::from(n), &[]);
+    }
+
+    fn block(plan: &mut Plan, task: u8, blockers: &[u8], n: u8) {
+        let action = Action::SetTaskBlockedBy {
+            task_id: entry(task),
+            blocked_by: blockers.iter().copied().map(entry).collect(),
+        };
+        apply(plan, action, n, u64::from(n), &[]);
+    }
+
+    #[test]
+    fn test_tasks_created_by_one_change_are_distinct() {
+        let mut p = plan();
+        for (index, subject) in ["First", "Second", "Third"].into_iter().enumerate() {
+            let action = Action::AddTask {
+                subject: subject.to_string(),
+                description: None,
+                estimate: None,
+                affected_files: vec![],
+            };
+            p.apply_action(action, entry(1), index, actor(), Timestamp::from_secs(1), &BTreeSet::new())
+                .unwrap();
+        }
+
+        let ids = p.tasks().iter().map(|t| t.id).collect::<BTreeSet<_>>();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(p.tasks()[0].id, entry(1));
+        assert_eq!(p.tasks()[1].id, task_id(entry(1), 1));
+    }
+
+    #[test]
+    fn test_batch_task_ids_match_plan_tasks() {
+        let mut batch = Batch::default();
+        batch
+            .add_task("Design", None, None, vec![])
+            .add_critical_file("src/lib.rs")
+            .add_task("Implement", None, None, vec![])
+            .add_milestone("Beta", None)
+            .add_task("Release", None, None, vec![]);
+
+        // Apply the batch as a single change, as `Plan::apply_op` does.
+        let mut p = plan();
+        let mut created = Created::default();
+        for action in batch.actions().iter().cloned() {
+            let index = created.index(&action);
+            p.apply_action(action, entry(5), index, actor(), Timestamp::from_secs(5), &BTreeSet::new())
+                .unwrap();
+        }
+
+        let ids = batch.task_ids(entry(5));
+        assert_eq!(ids, p.tasks().iter().map(|t| t.id).collect::<Vec<_>>());
+        assert_eq!(ids.iter().collect::<BTreeSet<_>>().len(), 3);
+        assert_eq!(ids[0], entry(5));
+        let subjects = ids.iter().map(|id| p.task(id).unwrap().subject.as_str()).collect::<Vec<_>>();
+        assert_eq!(subjects, vec!["Design", "Implement", "Release"]);
+    }
+
+    #[test]
+    fn test_blocked_by_rejects_self_missing_and_cycles() {
+        let mut p = plan();
+        add_task(&mut p, 1);
+        add_task(&mut p, 2);
+        add_task(&mut p, 3);
+
+        block(&mut p, 2, &[1], 4);
+        block(&mut p, 3, &[2], 5);
+        // Task 1 being blocked by task 3 would close the cycle 1 -> 2 -> 3 -> 1.
+        block(&mut p, 1, &[1, 9, 3], 6);
+
+        assert!(p.task(&entry(1)).unwrap().blocked_by.is_empty());
+        let kinds = p.dependency_errors().iter().map(|e| (e.blocker, e.kind)).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (entry(1), DependencyErrorKind::SelfReference),
+                (entry(9), DependencyErrorKind::MissingTask),
+                (entry(3), DependencyErrorKind::Cycle),
+            ]
+        );
+        assert_eq!(p.unblocked_tasks().map(|t| t.id).collect::<Vec<_>>(), vec![entry(1)]);
+    }
+
+    #[test]
+    fn test_remove_task_prunes_blockers() {
+        let mut p = plan();
+        add_task(&mut p, 1);
+        add_task(&mut p, 2);
+
+        block(&mut p, 2, &[1], 3);
+        apply(&mut p, Action::RemoveTask { task_id: entry(1) }, 4, 4, &[]);
+
+        let task = p.task(&entry(2)).unwrap();
+        assert!(!task.is_blocked());
+        assert_eq!(p.unblocked_tasks().count(), 1);
+    }
+
+    #[test]
+    fn test_concurrent_add_wins_over_remove() {
+        let path = "src/lib.rs".to_string();
+
+        let mut p = plan();
+        apply(&mut p, Action::AddCriticalFile { path: path.clone() }, 1, 10, &[]);
+        // Removal and re-addition happen concurrently; the addition survives.
+        apply(&mut p, Action::AddCriticalFile { path: path.clone() }, 2, 20, &[]);
+        apply(&mut p, Action::RemoveCriticalFile { path: path.clone() }, 3, 30, &[2]);
+        assert!(p.critical_files().any(|f| f == &path));
+
+        // A removal that has seen every addition removes the element.
+        apply(&mut p, Action::RemoveCriticalFile { path: path.clone() }, 4, 40, &[]);
+        assert!(p.critical_files().next().is_none());
+    }
+
+    #[test]
+    fn test_effective_status() {
+        let mut p = plan();
+        add_task(&mut p, 1);
+        add_task(&mut p, 2);
+        apply(&mut p, Action::LinkTaskToCommit { task_id: entry(1), commit: entry(10) }, 3, 3, &[]);
+        // Drafts need approval first.
+        assert_eq!(p.effective_status(), PlanStatus::Draft);
+
+        apply(&mut p, Action::SetStatus { status: PlanStatus::Approved, force: false }, 4, 4, &[]);
+        assert_eq!(p.effective_status(), PlanStatus::InProgress);
+
+        apply(&mut p, Action::LinkTaskToCommit { task_id: entry(2), commit: entry(11) }, 5, 5, &[]);
+        assert_eq!(p.effective_status(), PlanStatus::Completed);
+
+        // Archived plans stay archived.
+        apply(&mut p, Action::SetStatus { status: PlanStatus::Archived, force: false }, 6, 6, &[]);
+        assert_eq!(p.effective_status(), PlanStatus::Archived);
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        let status = |status| Action::SetStatus { status, force: false };
+        let set = |p: &mut Plan, action, n: u8| {
+            p.apply_action(action, entry(n), 0, actor(), Timestamp::from_secs(u64::from(n)), &BTreeSet::new())
+        };
+
+        let mut p = plan();
+        add_task(&mut p, 1);
+        set(&mut p, status(PlanStatus::Completed), 2).unwrap();
+        assert_eq!(p.status(), &PlanStatus::Draft);
+        set(&mut p, status(PlanStatus::Approved), 3).unwrap();
+        set(&mut p, status(PlanStatus::InProgress), 4).unwrap();
+        assert_eq!(p.check_transition(PlanStatus::Completed, false), Err(TransitionError::TasksNotDone(1)));
+        assert_eq!(p.check_transition(PlanStatus::Completed, true), Ok(()));
+
+        // Pending tasks are only checked when making the change, not when applying it.
+        set(&mut p, status(PlanStatus::Completed), 6).unwrap();
+        assert_eq!(p.status(), &PlanStatus::Completed);
+        set(&mut p, status(PlanStatus::Archived), 7).unwrap();
+        set(&mut p, status(PlanStatus::InProgress), 8).unwrap();
+        assert_eq!(p.status(), &PlanStatus::Archived);
+        assert!(PlanStatus::Archived.requires_delegate(PlanStatus::Draft));
+    }
+
+    #[test]
+    fn test_disallowed_transition_keeps_later_changes() {
+        let status = |status| Action::SetStatus { status, force: false };
+        let describe = Action::EditDescription { description: "Later".to_string(), embeds: vec![] };
+
+        // Another peer moves the draft straight to in progress, which the state machine
+        // doesn't allow, then keeps editing on top of that change.
+        let mut p = plan();
+        apply(&mut p, status(PlanStatus::InProgress), 1, 1, &[]);
+        apply(&mut p, describe, 2, 2, &[]);
+
+        // The transition is ignored, and doesn't count as the latest status change;
+        // the changes built on it still apply.
+        assert_eq!(p.status(), &PlanStatus::Draft);
+        assert_eq!(p.description(), "Later");
+        apply(&mut p, status(PlanStatus::Approved), 3, 3, &[]);
+        assert_eq!(p.status(), &PlanStatus::Approved);
+    }
+
+    #[test]
+    fn test_completion_concurrent_with_add_task() {
+        let status = |status| Action::SetStatus { status, force: false };
+        let link = Action::LinkTaskToCommit { task_id: entry(2), commit: entry(0xc0) };
+        let task = Action::AddTask {
+            subject: "Late task".to_string(),
+            description: None,
+            estimate: None,
+            affected_files: vec![],
+        };
+        let base = |p: &mut Plan| {
+            apply(p, status(PlanStatus::Approved), 1, 1, &[]);
+            add_task(p, 2);
+            apply(p, link.clone(), 3, 3, &[]);
+        };
+
+        // One peer completes the plan with all of its tasks done, while another adds a
+        // task. Both replication orders converge, whichever change is applied first.
+        let mut a = plan();
+        base(&mut a);
+        apply(&mut a, status(PlanStatus::Completed), 4, 4, &[]);
+        apply(&mut a, task.clone(), 5, 5, &[4]);
+
+        let mut b = plan();
+        base(&mut b);
+        apply(&mut b, task, 5, 5, &[]);
+        apply(&mut b, status(PlanStatus::Completed), 4, 4, &[5]);
+
+        for p in [&a, &b] {
+            assert_eq!(p.status(), &PlanStatus::Completed);
+            assert_eq!(p.tasks().len(), 2);
+        }
+        assert!(!b.all_tasks_complete());
+    }
+
+    #[test]
+    fn test_task_parents() {
+        let parent = |p: &Plan, n: u8| p.task(&entry(n)).unwrap().parent;
+        let mut p = plan();
+        add_task(&mut p, 1);
+        add_task(&mut p, 2);
+        add_task(&mut p, 3);
+
+        apply(&mut p, Action::SetTaskParent { task_id: entry(2), parent: Some(entry(1)) }, 4, 4, &[]);
+        apply(&mut p, Action::SetTaskParent { task_id: entry(3), parent: Some(entry(2)) }, 5, 5, &[]);
+        assert_eq!(parent(&p, 3), Some(entry(2)));
+
+        // Cycles, self-references and missing parents are ignored.
+        apply(&mut p, Action::SetTaskParent { task_id: entry(1), parent: Some(entry(3)) }, 6, 6, &[]);
+        apply(&mut p, Action::SetTaskParent { task_id: entry(1), parent: Some(entry(1)) }, 7, 7, &[]);
+        apply(&mut p, Action::SetTaskParent { task_id: entry(1), parent: Some(entry(9)) }, 8, 8, &[]);
+        assert_eq!(parent(&p, 1), None);
+
+        // Subtasks of a removed task move up to its parent.
+        apply(&mut p, Action::RemoveTask { task_id: entry(2) }, 9, 9, &[]);
+        assert_eq!(parent(&p, 3), Some(entry(1)));
+    }
+
+    #[test]
+    fn test_milestones() {
+        let milestone = |p: &Plan, n: u8| p.task(&entry(n)).unwrap().milestone;
+        let mut p = plan();
+        add_task(&mut p, 1);
+        add_task(&mut p, 2);
+        let (alpha, beta) = (milestone_id(entry(3), 0), milestone_id(entry(3), 1));
+        for (index, name) in ["Alpha", "Beta"].into_iter().enumerate() {
+            let action = Action::AddMilestone { name: name.to_string(), due: None };
+            p.apply_action(action, entry(3), index, actor(), Timestamp::from_secs(3), &BTreeSet::new())
+                .unwrap();
+        }
+        assert_ne!(alpha, beta);
+        assert_ne!(alpha, task_id(entry(3), 0));
+
+        apply(&mut p, Action::SetTaskMilestone { task_id: entry(1), milestone_id: Some(alpha) }, 4, 4, &[]);
+        apply(&mut p, Action::SetTaskMilestone { task_id: entry(2), milestone_id: Some(entry(9)) }, 5, 5, &[]);
+        assert_eq!(milestone(&p, 1), Some(alpha));
+        assert_eq!(milestone(&p, 2), None);
+
+        let due = "2030-01-31".parse().ok();
+        apply(&mut p, Action::EditMilestone { milestone_id: alpha, name: None, due: Some(due) }, 6, 6, &[]);
+        apply(&mut p, Action::ReorderMilestones { milestone_ids: vec![beta, alpha] }, 7, 7, &[]);
+        let names = p.milestones().iter().map(|m| m.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["Beta", "Alpha"]);
+        assert_eq!(p.milestone(&alpha).unwrap().due, due);
+
+        // Tasks of a removed milestone are kept, without a milestone.
+        apply(&mut p, Action::RemoveMilestone { milestone_id: alpha }, 8, 8, &[]);
+        assert!(p.milestone(&alpha).is_none());
+        assert_eq!(milestone(&p, 1), None);
+        assert_eq!(p.tasks().len(), 2);
+    }
+
+    #[test]
+    fn test_task_assignees() {
+        let alice = Did::from(actor());
+        let bob = Did::from_str("did:key:z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi").unwrap();
+        let assign = |add: &[Did], remove: &[Did]| Action::AssignTask {
+            task_id: entry(1),
+            add: add.iter().copied().collect(),
+            remove: remove.iter().copied().collect(),
+        };
+        let mut p = plan();
+        add_task(&mut p, 1);
+        add_task(&mut p, 2);
+
+        apply(&mut p, assign(&[alice, bob], &[]), 3, 3, &[]);
+        assert_eq!(p.tasks_assigned_to(&bob).map(|t| t.id).collect::<Vec<_>>(), vec![entry(1)]);
+
+        // Bob re-assigns himself while being unassigned; the assignment survives.
+        apply(&mut p, assign(&[bob], &[]), 4, 4, &[]);
+        apply(&mut p, assign(&[], &[bob]), 5, 5, &[4]);
+        assert!(p.task(&entry(1)).unwrap().is_assigned_to(&bob));
+
+        apply(&mut p, assign(&[], &[alice, bob]), 6, 6, &[]);
+        assert!(p.task(&entry(1)).unwrap().assignees.is_empty());
+        assert_eq!(p.tasks_assigned_to(&alice).count(), 0);
+    }
+
+    #[test]
+    fn test_approvals() {
+        let mut p = plan();
+        assert_eq!(p.revision(), entry(0));
+
+        add_task(&mut p, 1);
+        apply(&mut p, Action::Approve { revision: entry(1) }, 2, 2, &[]);
+        assert_eq!(p.revision(), entry(1));
+        assert_eq!(p.approvals().next().map(|(_, a)| a.revision), Some(entry(1)));
+
+        // Comments and links don't revise the plan; edits do, making approvals stale.
+        apply(&mut p, Action::LinkIssue { issue_id: ObjectId::from(entry(10)) }, 3, 3, &[]);
+        assert_eq!(p.revision(), entry(1));
+        apply(&mut p, Action::EditTitle { title: Title::try_from("Revised".to_string()).unwrap() }, 4, 4, &[]);
+        assert_eq!(p.revision(), entry(4));
+        assert!(p.approvals().all(|(_, a)| a.revision != p.revision()));
+
+        apply(&mut p, Action::Unapprove, 5, 5, &[]);
+        assert_eq!(p.approvals().count(), 0);
+    }
+
+    #[test]
+    fn test_status_cannot_approve_without_quorum() {
+        let doc: Doc = serde_json::from_value(serde_json::json!({
+            "payload": {
+                "xyz.radicle.project": { "name": "plans", "description": "", "defaultBranch": "master" }
+            },
+            "delegates": [Did::from(actor())],
+            "threshold": 1,
+        }))
+        .unwrap();
+        let mut p = plan();
+
+        // Neither a delegate's status change nor an override approves the draft alone.
+        for force in [false, true] {
+            assert!(p.lacks_approval(&Action::SetStatus { status: PlanStatus::Approved, force }, &doc));
+        }
+        assert!(!p.lacks_approval(&Action::SetStatus { status: PlanStatus::Archived, force: false }, &doc));
+
+        let revision = p.revision();
+        apply(&mut p, Action::Approve { revision }, 1, 1, &[]);
+        assert!(!p.lacks_approval(&Action::SetStatus { status: PlanStatus::Approved, force: false }, &doc));
+    }
+
+    #[test]
+    fn test_references() {
+        let id = |n| ObjectId::from(entry(n));
+        let mut p = plan();
+        add_task(&mut p, 1);
+        apply(&mut p, Action::LinkIssue { issue_id: id(10) }, 2, 2, &[]);
+        apply(&mut p, Action::LinkTaskToIssue { task_id: entry(1), issue_id: id(11) }, 3, 3, &[]);
+        apply(&mut p, Action::LinkPatch { patch_id: id(12) }, 4, 4, &[]);
+        apply(&mut p, Action::LinkTaskToCommit { task_id: entry(1), commit: entry(13) }, 5, 5, &[]);
+        apply(&mut p, Action::AddCriticalFile { path: "src/cache/".to_string() }, 6, 6, &[]);
+        apply(&mut p, Action::LinkTaskToPatch { task_id: entry(1), patch_id: id(14) }, 7, 7, &[]);
+
+        assert!(p.references_issue(&id(10)));
+        assert!(p.references_issue(&id(11)));
+        assert!(!p.references_issue(&id(12)));
+        assert!(p.references_patch(&id(12)));
+        assert!(p.references_patch(&id(14)));
+        assert_eq!(p.task(&entry(1)).unwrap().linked_patch, Some(id(14)));
+        assert!(p.references_commit(&entry(13)));
+        assert!(!p.references_commit(&entry(10)));
+
+        assert!(p.references_file("src/cache"));
+        assert!(p.references_file("./src/cache/sqlite.rs"));
+        assert!(!p.references_file("src/cache.rs"));
+    }
+
+    #[test]
+    fn test_plan_diff() {
+        let mut old = plan();
+        add_task(&mut old, 1);
+        add_task(&mut old, 2);
+
+        let mut new = old.clone();
+        apply(&mut new, Action::SetStatus { status: PlanStatus::Approved, force: false }, 3, 3, &[]);
+        apply(&mut new, Action::RemoveTask { task_id: entry(1) }, 4, 4, &[]);
+        add_task(&mut new, 5);
+        let edit = Action::EditTask {
+            task_id: entry(2),
+            subject: Some("Renamed".to_string()),
+            description: None,
+            estimate: None,
+            affected_files: None,
+        };
+        apply(&mut new, edit, 6, 6, &[]);
+        apply(&mut new, Action::LinkPatch { patch_id: ObjectId::from(entry(7)) }, 7, 7, &[]);
+
+        let diff = PlanDiff::new(&old, &new);
+        assert_eq!(diff.status.as_ref().map(|c| c.new), Some(PlanStatus::Approved));
+        assert_eq!(diff.tasks_removed.iter().map(|t| t.id).collect::<Vec<_>>(), vec![entry(1)]);
+        assert_eq!(diff.tasks_added.iter().map(|t| t.id).collect::<Vec<_>>(), vec![entry(5)]);
+        assert_eq!(diff.tasks_edited.len(), 1);
+        assert_eq!(diff.tasks_edited[0].subject, "Renamed");
+        assert_eq!(diff.patches.added, vec![ObjectId::from(entry(7))]);
+        assert!(diff.title.is_none());
+        assert!(!diff.tasks_reordered);
+
+        assert!(PlanDiff::new(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_plan_diff_milestones() {
+        let mut old = plan();
+        let (alpha, beta) = (milestone_id(entry(1), 0), milestone_id(entry(1), 1));
+        for (index, name) in ["Alpha", "Beta"].into_iter().enumerate() {
+            let action = Action::AddMilestone { name: name.to_string(), due: None };
+            old.apply_action(action, entry(1), index, actor(), Timestamp::from_secs(1), &BTreeSet::new())
+                .unwrap();
+        }
+
+        let mut new = old.clone();
+        let due = "2030-01-31".parse().ok();
+        let edit = Action::EditMilestone { milestone_id: alpha, name: Some("First".to_string()), due: Some(due) };
+        apply(&mut new, edit, 2, 2, &[]);
+
+        let diff = PlanDiff::new(&old, &new);
+        assert_eq!(diff.milestones_edited.len(), 1);
+        assert_eq!(diff.milestones_edited[0].name, "First");
+        assert_eq!(diff.milestones_edited[0].name_change.as_ref().map(|c| c.old.as_str()), Some("Alpha"));
+        assert_eq!(diff.milestones_edited[0].due.as_ref().map(|c| (c.old, c.new)), Some((None, due)));
+        assert!(!diff.milestones_reordered);
+
+        let mut reordered = old.clone();
+        apply(&mut reordered, Action::ReorderMilestones { milestone_ids: vec![beta, alpha] }, 3, 3, &[]);
+        let diff = PlanDiff::new(&old, &reordered);
+        assert!(diff.milestones_reordered);
+        assert!(diff.milestones_edited.is_empty());
+        assert!(!diff.is_empty());
+    }
+}
@@ -1,34 +1,88 @@
 //! rad-plan CLI tool for managing Plan COBs.
 //!
 //! Usage:
-//!   rad-plan open <title> [--description <desc>]
-//!   rad-plan list [--status <status>]
-//!   rad-plan show <id>
-//!   rad-plan task add <plan-id> <subject> [--description <desc>]
+//!   rad-plan open [<title>] [--description <desc>] [--labels <label>] [--assign <did>] [--task <subject>] [--issue <id>]
+//!   rad-plan open --template <name> [--var <key>=<value>]... [<title>]
+//!   rad-plan list [--status <status>] [--label <label>]
+//!   rad-plan mine
+//!   rad-plan show <id> [--critical-path]
+//!   rad-plan log <id> [--json]
+//!   rad-plan diff <id> [<from>] [<to>] [--json]
+//!   rad-plan status <id> <status> [--force] | --auto
+//!   rad-plan approve <id> [--revoke]
+//!   rad-plan reconcile [<plan-id>]
+//!   rad-plan task add <plan-id> <subject>... [--description <desc>] [--parent <task-id>] [--milestone <milestone>]
 //!   rad-plan task link-commit <plan-id> <task-id> --commit <oid>
-//!   rad-plan task list <plan-id>
+//!   rad-plan task link <plan-id> <task-id> [--issue <issue-id>] [--patch <patch-id>]
+//!   rad-plan task list <plan-id> [--assignee <did|me>]
+//!   rad-plan task graph <plan-id>
 //!   rad-plan link --issue <issue-id> <plan-id>
 //!   rad-plan link --patch <patch-id> <plan-id>
+//!   rad-plan task reorder <plan-id> <task-id>...
+//!   rad-plan task block <plan-id> <task-id> <blocker-id>...
+//!   rad-plan task unblock <plan-id> <task-id> [<blocker-id>...]
+//!   rad-plan task parent <plan-id> <task-id> [<parent-id>]
+//!   rad-plan task milestone <plan-id> <task-id> [<milestone>]
+//!   rad-plan task assign <plan-id> <task-id> [--add <did|me>] [--delete <did|me>]
+//!   rad-plan milestone add <plan-id> <name> [--due <YYYY-MM-DD>]
+//!   rad-plan milestone edit <plan-id> <milestone> [--name <name>] [--due <YYYY-MM-DD> | --no-due]
+//!   rad-plan milestone remove <plan-id> <milestone>
+//!   rad-plan milestone reorder <plan-id> <milestone>...
+//!   rad-plan milestone list <plan-id>
+//!   rad-plan file add|remove <plan-id> <path>
+//!   rad-plan label <plan-id> [--add <label>] [--delete <label>]
+//!   rad-plan assign <plan-id> [--add <did>] [--delete <did>]
 //!   rad-plan comment <plan-id> <message>
+//!   rad-plan comment edit <plan-id> <comment-id> <message>
+//!   rad-plan comment redact <plan-id> <comment-id>
 //!   rad-plan export <plan-id> [--format md|json]
-
+//!   rad-plan import <file.md>
+//!   rad-plan apply <plan-id> <file.md> [--dry-run]
+//!   rad-plan sync-commits <plan-id> [<revspec>]
+//!   rad-plan verify <plan-id> [--json]
+//!   rad-plan which <id-or-path>
+//!   rad-plan cache [<plan-id>]
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, IsTerminal};
 use std::path::PathBuf;
 use std::process::ExitCode;
 use std::str::FromStr;
 
 use clap::{Parser, Subcommand};
 
+use radicle::cob::common::Label;
 use radicle::cob::thread::CommentId;
-use radicle::cob::{self, ObjectId, TypeName};
+use radicle::cob::{self, ObjectId, Store as _, TypeName};
+use radicle::prelude::Did;
 use radicle::profile::Profile;
 use radicle::rad;
 use radicle::storage::git::Repository;
-use radicle::storage::ReadStorage;
+use radicle::storage::{ReadRepository, ReadStorage};
 
-use radicle_plan_cob::{Plan, PlanId, PlanStatus, Plans, TaskId, TYPENAME};
+use radicle_plan_cob::cache::{self, PlanCache};
+use radicle_plan_cob::diff::{DiffLine, SetChange};
+use radicle_plan_cob::document::{DocumentError, DocumentTask, MilestoneRef, TaskRef};
+use radicle_plan_cob::template::{self, PlanTemplate};
+use radicle_plan_cob::{
+    Action, CommitStatus, DueDate, Estimate, Milestone, MilestoneId, Plan, PlanDiff, PlanDocument, PlanEvent, PlanId,
+    PlanStatus, Plans, PlansMut, TaskId, TYPENAME,
+};
 
 const MIN_PREFIX_LEN: usize = 7;
 
+/// Instructions at the top of the buffer opened in `$EDITOR`; removed before parsing.
+const EDITOR_HELP: &str = "\
+<!--
+Write the plan as Markdown: a `# Title` line, then the description, then a
+`## Tasks` checklist of `- [ ] Subject _(estimate)_` items, with the task
+description as nested bullets. Removing a task's line removes the task.
+Checking or unchecking a box has no effect: a task is done once it is linked
+to a commit or a merged patch, eg. with `rad-plan task link-commit`.
+Leave the title empty to abort.
+-->
+";
+
 /// rad-plan: Manage implementation plans as Radicle COBs
 #[derive(Parser)]
 #[command(name = "rad-plan")]
@@ -46,16 +100,36 @@ struct Cli {
 enum Commands {
     /// Create a new plan
     Open {
-        /// Plan title
-        title: String,
+        /// Plan title (prompted for in the editor if omitted)
+        title: Option<String>,
 
-        /// Plan description
+        /// Plan description (opens $EDITOR if omitted)
         #[arg(short, long)]
         description: Option<String>,
 
         /// Labels to apply
         #[arg(short, long)]
         labels: Vec<String>,
+
+        /// DIDs to assign
+        #[arg(short, long)]
+        assign: Vec<String>,
+
+        /// Tasks to add, by subject
+        #[arg(short, long)]
+        task: Vec<String>,
+
+        /// Issues to link
+        #[arg(long)]
+        issue: Vec<String>,
+
+        /// Start from a template in `.radicle/plans`, by name
+        #[arg(long)]
+        template: Option<String>,
+
+        /// Template variables, as `key=value`
+        #[arg(long = "var", value_name = "KEY=VALUE", requires = "template")]
+        vars: Vec<String>,
     },
 
     /// List all plans
@@ -64,11 +138,18 @@ enum Commands {
         #[arg(short, long)]
         status: Option<String>,
 
+        /// Only show plans with this label
+        #[arg(short, long)]
+        label: Option<String>,
+
         /// Show all plans including archived
         #[arg(short, long)]
         all: bool,
     },
 
+    /// List the unblocked tasks assigned to you, across all plans
+    Mine,
+
     /// Show plan details
     Show {
         /// Plan ID (short form or full ID)
@@ -77,6 +158,36 @@ enum Commands {
         /// Show in JSON format
         #[arg(long)]
         json: bool,
+
+        /// Show the chain of tasks that gates plan completion
+        #[arg(long)]
+        critical_path: bool,
+    },
+
+    /// Show the history of a plan
+    Log {
+        /// Plan ID (short form or full ID)
+        id: String,
+
+        /// Show in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show what changed in a plan between two revisions
+    Diff {
+        /// Plan ID (short form or full ID)
+        id: String,
+
+        /// Change to compare from (defaults to the plan's root change)
+        from: Option<String>,
+
+        /// Change to compare to (defaults to the latest state)
+        to: Option<String>,
+
+        /// Show in JSON format
+        #[arg(long)]
+        json: bool,
     },
 
     /// Set plan status
@@ -85,7 +196,26 @@ enum Commands {
         id: String,
 
         /// New status (draft, approved, in-progress, completed, archived)
-        status: String,
+        #[arg(required_unless_present = "auto")]
+        status: Option<String>,
+
+        /// Set the status implied by the plan's tasks, if the plan lags behind
+        #[arg(long, conflicts_with = "status")]
+        auto: bool,
+
+        /// Complete the plan even though not all tasks are done
+        #[arg(long, conflicts_with = "auto")]
+        force: bool,
+    },
+
+    /// Approve the current revision of a plan (delegates only)
+    Approve {
+        /// Plan ID
+        id: String,
+
+        /// Withdraw your approval instead
+        #[arg(long)]
+        revoke: bool,
     },
 
     /// Manage plan tasks
@@ -94,6 +224,12 @@ enum Commands {
         command: TaskCommands,
     },
 
+    /// Manage plan milestones
+    Milestone {
+        #[command(subcommand)]
+        command: MilestoneCommands,
+    },
+
     /// Link a COB to the plan
     Link {
         /// Plan ID
@@ -122,19 +258,78 @@ enum Commands {
         patch: Option<String>,
     },
 
-    /// Add a comment to the plan
+    /// Add, edit or redact comments on the plan
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Comment {
+        #[command(subcommand)]
+        command: Option<CommentCommands>,
+
         /// Plan ID
-        plan_id: String,
+        #[arg(required = true)]
+        plan_id: Option<String>,
 
         /// Comment message
-        message: String,
+        #[arg(required = true)]
+        message: Option<String>,
 
         /// Reply to a specific comment ID
         #[arg(long)]
         reply_to: Option<String>,
     },
 
+    /// Manage the plan's critical files
+    File {
+        #[command(subcommand)]
+        command: FileCommands,
+    },
+
+    /// Add or remove plan labels
+    Label {
+        /// Plan ID
+        plan_id: String,
+
+        /// Label to add
+        #[arg(long)]
+        add: Vec<String>,
+
+        /// Label to remove
+        #[arg(long)]
+        delete: Vec<String>,
+    },
+
+    /// Add or remove plan assignees
+    Assign {
+        /// Plan ID
+        plan_id: String,
+
+        /// DID to assign
+        #[arg(long)]
+        add: Vec<String>,
+
+        /// DID to unassign
+        #[arg(long)]
+        delete: Vec<String>,
+    },
+
+    /// Create a plan from a Markdown checklist
+    Import {
+        /// Markdown file, in the format written by `rad-plan export`
+        file: PathBuf,
+    },
+
+    /// Update a plan to match an edited Markdown checklist
+    Apply {
+        /// Plan ID
+        plan_id: String,
+
+        /// Markdown file, in the format written by `rad-plan export`
+        file: PathBuf,
+
+        /// Print the changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Export plan to another format
     Export {
         /// Plan ID
@@ -149,7 +344,7 @@ enum Commands {
         output: Option<PathBuf>,
     },
 
-    /// Edit plan title or description
+    /// Edit plan title or description, or the whole plan in $EDITOR
     Edit {
         /// Plan ID
         id: String,
@@ -162,6 +357,46 @@ enum Commands {
         #[arg(long)]
         description: Option<String>,
     },
+
+    /// Link tasks to commits from `Plan-Task` and `Closes-Task` commit trailers
+    ///
+    /// Commits are read from the working copy; those not pushed to storage yet are
+    /// linked with a warning.
+    SyncCommits {
+        /// Plan ID (short form allowed)
+        plan_id: String,
+
+        /// Commits to scan, eg. `main..feature` (defaults to the history of HEAD)
+        revspec: Option<String>,
+    },
+
+    /// Check that linked commits exist and are merged into the default branch
+    Verify {
+        /// Plan ID (short form allowed)
+        plan_id: String,
+
+        /// Output the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Find the plans referencing an issue, patch, commit or file
+    Which {
+        /// Issue, patch or commit ID (short form allowed), or a file path
+        target: String,
+    },
+
+    /// Update plan statuses that lag behind their tasks
+    Reconcile {
+        /// Only reconcile this plan
+        id: Option<String>,
+    },
+
+    /// Rebuild the plan cache from the repository
+    Cache {
+        /// Only re-cache this plan
+        id: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -171,26 +406,45 @@ enum TaskCommands {
         /// Plan ID
         plan_id: String,
 
-        /// Task subject
-        subject: String,
+        /// Task subjects; several tasks are added in a single change
+        #[arg(required = true)]
+        subjects: Vec<String>,
 
-        /// Task description
+        /// Task description, given to every task added
         #[arg(short, long)]
         description: Option<String>,
 
-        /// Time estimate
+        /// Estimate, eg. 2h, 1d, 1w 2d or 3sp, given to every task added
         #[arg(short, long)]
-        estimate: Option<String>,
+        estimate: Option<Estimate>,
 
-        /// Affected files
+        /// Affected files, given to every task added
         #[arg(short, long)]
         files: Vec<String>,
+
+        /// Add the tasks as subtasks of this task
+        #[arg(long)]
+        parent: Option<String>,
+
+        /// Add the tasks to this milestone, by ID or name
+        #[arg(long)]
+        milestone: Option<String>,
     },
 
     /// List tasks in a plan
     List {
         /// Plan ID
         plan_id: String,
+
+        /// Only show tasks assigned to this DID, or to you with `me`
+        #[arg(long)]
+        assignee: Option<String>,
+    },
+
+    /// Show tasks in dependency order, with depth, slack and the critical path
+    Graph {
+        /// Plan ID
+        plan_id: String,
     },
 
     /// Link a task to a commit (marks the task as done)
@@ -222,9 +476,9 @@ enum TaskCommands {
         #[arg(short, long)]
         description: Option<String>,
 
-        /// New estimate
+        /// New estimate, eg. 2h, 1d, 1w 2d or 3sp
         #[arg(short, long)]
-        estimate: Option<String>,
+        estimate: Option<Estimate>,
 
         /// Affected files (replaces existing list)
         #[arg(short, long)]
@@ -240,7 +494,7 @@ enum TaskCommands {
         task_id: String,
     },
 
-    /// Link a task to a Radicle issue
+    /// Link a task to a Radicle issue, or to the patch that completes it
     Link {
         /// Plan ID
         plan_id: String,
@@ -249,8 +503,195 @@ enum TaskCommands {
         task_id: String,
 
         /// Issue ID to link
+        #[arg(long, required_unless_present = "patch")]
+        issue: Option<String>,
+
+        /// Patch ID to link (the task is done once the patch is merged)
+        #[arg(long)]
+        patch: Option<String>,
+    },
+
+    /// Move tasks to the front of the plan, in the given order
+    Reorder {
+        /// Plan ID
+        plan_id: String,
+
+        /// Task IDs, in their new order
+        #[arg(required = true)]
+        task_ids: Vec<String>,
+    },
+
+    /// Mark a task as blocked by other tasks
+    Block {
+        /// Plan ID
+        plan_id: String,
+
+        /// Task ID
+        task_id: String,
+
+        /// IDs of the blocking tasks
+        #[arg(required = true)]
+        blocked_by: Vec<String>,
+    },
+
+    /// Remove blockers from a task (all blockers, if none are given)
+    Unblock {
+        /// Plan ID
+        plan_id: String,
+
+        /// Task ID
+        task_id: String,
+
+        /// IDs of the blocking tasks to remove
+        blocked_by: Vec<String>,
+    },
+
+    /// Make a task a subtask of another (a top-level task, if no parent is given)
+    Parent {
+        /// Plan ID
+        plan_id: String,
+
+        /// Task ID
+        task_id: String,
+
+        /// ID of the parent task
+        parent: Option<String>,
+    },
+
+    /// Add a task to a milestone (or remove it from its milestone, if none is given)
+    Milestone {
+        /// Plan ID
+        plan_id: String,
+
+        /// Task ID
+        task_id: String,
+
+        /// Milestone ID or name
+        milestone: Option<String>,
+    },
+
+    /// Add or remove task assignees
+    Assign {
+        /// Plan ID
+        plan_id: String,
+
+        /// Task ID
+        task_id: String,
+
+        /// DID to assign, or `me`
+        #[arg(long)]
+        add: Vec<String>,
+
+        /// DID to unassign, or `me`
+        #[arg(long)]
+        delete: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MilestoneCommands {
+    /// Add a milestone to a plan
+    Add {
+        /// Plan ID
+        plan_id: String,
+
+        /// Milestone name
+        name: String,
+
+        /// Due date, as YYYY-MM-DD
+        #[arg(long)]
+        due: Option<DueDate>,
+    },
+
+    /// Edit a milestone
+    Edit {
+        /// Plan ID
+        plan_id: String,
+
+        /// Milestone ID or name
+        milestone: String,
+
+        /// New name
+        #[arg(long)]
+        name: Option<String>,
+
+        /// New due date, as YYYY-MM-DD
         #[arg(long)]
-        issue: String,
+        due: Option<DueDate>,
+
+        /// Remove the due date
+        #[arg(long, conflicts_with = "due")]
+        no_due: bool,
+    },
+
+    /// Remove a milestone (its tasks are kept, without a milestone)
+    Remove {
+        /// Plan ID
+        plan_id: String,
+
+        /// Milestone ID or name
+        milestone: String,
+    },
+
+    /// Move milestones to the front of the plan, in the given order
+    Reorder {
+        /// Plan ID
+        plan_id: String,
+
+        /// Milestone IDs or names, in their new order
+        #[arg(required = true)]
+        milestones: Vec<String>,
+    },
+
+    /// List the milestones of a plan, with their completion
+    List {
+        /// Plan ID
+        plan_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum FileCommands {
+    /// Mark a file as critical to the plan
+    Add {
+        /// Plan ID
+        plan_id: String,
+
+        /// File path
+        path: String,
+    },
+
+    /// Unmark a critical file
+    Remove {
+        /// Plan ID
+        plan_id: String,
+
+        /// File path
+        path: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum CommentCommands {
+    /// Edit a comment
+    Edit {
+        /// Plan ID
+        plan_id: String,
+
+        /// Comment ID
+        comment_id: String,
+
+        /// New comment message
+        message: String,
+    },
+
+    /// Redact a comment
+    Redact {
+        /// Plan ID
+        plan_id: String,
+
+        /// Comment ID
+        comment_id: String,
     },
 }
 
@@ -272,7 +713,7 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     // Load profile and get repository
     let profile = Profile::load()?;
 
-    let (_, rid) = if let Some(path) = cli.repo {
+    let (working, rid) = if let Some(path) = cli.repo {
         rad::at(&path)?
     } else {
         rad::cwd()?
@@ -281,32 +722,126 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     let repo = profile.storage.repository(rid)?;
 
     match cli.command {
-        Commands::Open { title, description, labels: _ } => {
-            let mut plans = Plans::open(&repo)?;
+        Commands::Open { title, description, labels, assign, task, issue, template, vars } => {
+            let issue_type: TypeName = "xyz.radicle.issue".parse().unwrap();
+            let mut plans = plans_mut(&profile, &repo)?;
             let signer = profile.signer()?;
-            let desc = description.unwrap_or_default();
 
-            let (id, plan) = plans.create(title.clone(), desc, vec![], &signer)?;
+            let mut doc = match template {
+                Some(name) => {
+                    let vars = vars
+                        .iter()
+                        .map(|v| v.split_once('=').map(|(k, v)| (k.trim().to_string(), v.to_string())))
+                        .collect::<Option<BTreeMap<_, _>>>()
+                        .ok_or("Template variables must be given as `key=value`")?;
+                    load_template(&name, &working, &repo)?.instantiate(&vars)?
+                }
+                None => PlanDocument::default(),
+            };
+            if let Some(title) = title {
+                doc.title = title;
+            }
+            if let Some(description) = &description {
+                doc.description = description.clone();
+            }
+            doc.tasks.extend(task.into_iter().map(DocumentTask::new));
+
+            if description.is_none() && io::stdin().is_terminal() && io::stdout().is_terminal() {
+                doc = edit_document(&doc)?.ok_or("Aborted: the plan has no title")?;
+            } else if doc.title.is_empty() {
+                return Err("A plan title is required".into());
+            }
+
+            let mut actions = Vec::new();
+            let labels = labels
+                .into_iter()
+                .chain(doc.metadata_list(template::LABELS_KEY))
+                .map(|l| Label::from_str(&l))
+                .collect::<Result<BTreeSet<_>, _>>()?;
+            if !labels.is_empty() {
+                actions.push(Action::Label { labels });
+            }
+            if !assign.is_empty() {
+                let assignees = assign.iter().map(|d| Did::from_str(d)).collect::<Result<_, _>>()?;
+                actions.push(Action::Assign { assignees });
+            }
+            for i in &issue {
+                let issue_id = resolve_cob_prefix(i, &issue_type, &repo)?;
+                actions.push(Action::LinkIssue { issue_id });
+            }
+            for path in doc.metadata_list(template::CRITICAL_FILES_KEY) {
+                actions.push(Action::AddCriticalFile { path });
+            }
+            for milestone in &doc.milestones {
+                actions.push(Action::AddMilestone {
+                    name: milestone.name.clone(),
+                    due: milestone.due,
+                });
+            }
+            for task in &doc.tasks {
+                actions.push(Action::AddTask {
+                    subject: task.subject.clone(),
+                    description: task.description.clone(),
+                    estimate: task.estimate.clone(),
+                    affected_files: vec![],
+                });
+            }
+
+            let (id, plan) = plans.create_with(doc.title.clone(), doc.description.clone(), vec![], actions, &signer)?;
 
             println!("Plan created: {}", id);
             println!("  Title: {}", plan.title());
             println!("  Status: {:?}", plan.status());
+            for task in plan.tasks() {
+                println!("  Task: {} ({})", task.subject, short_id(&task.id.into()));
+            }
+
+            // Task and milestone IDs derive from the root change, so blockers, parents
+            // and milestones can only be set after it.
+            let task_ids = plan.tasks().iter().map(|t| t.id).collect::<Vec<_>>();
+            let milestone_ids = plan.milestones().iter().map(|m| m.id).collect::<Vec<_>>();
+            let blockers = doc.blockers(&task_ids);
+            let parents = doc.parents(&task_ids);
+            let milestones = doc.task_milestones(&task_ids, &milestone_ids);
+            if !blockers.is_empty() || !parents.is_empty() || !milestones.is_empty() {
+                let mut plan = plans.get_mut(&id)?;
+                plan.batch("Arrange tasks", &signer, |b| {
+                    for (task, blocked_by) in blockers {
+                        b.set_task_blocked_by(task, blocked_by);
+                    }
+                    for (task, parent) in parents {
+                        b.set_task_parent(task, Some(parent));
+                    }
+                    for (task, milestone) in milestones {
+                        b.set_task_milestone(task, Some(milestone));
+                    }
+                })?;
+            }
         }
-        Commands::List { status, all } => {
-            let plans = Plans::open(&repo)?;
-            let counts = plans.counts()?;
+        Commands::List { status, label, all } => {
+            // Plans fetched or updated by other peers since they were cached are
+            // re-evaluated first.
+            let mut plans = plans_mut(&profile, &repo)?;
+            plans.refresh()?;
+            let cache = plans.cache();
+            let counts = cache.counts(&rid)?;
 
             println!("Plans ({} total, {} active):", counts.total(), counts.active());
             println!();
 
             let status_filter = status.as_ref().map(|s| parse_plan_status(s));
+            let label = label.as_deref().map(Label::from_str).transpose()?;
 
-            for result in plans.all()? {
-                let (id, plan) = result?;
+            let listed = match (&status_filter, &label) {
+                (Some(status), _) => cache.list_by_status(&rid, status)?,
+                (None, Some(label)) => cache.list_by_label(&rid, label)?,
+                (None, None) => cache.list(&rid)?,
+            };
 
-                // Filter by status if specified
-                if let Some(filter) = &status_filter {
-                    if plan.status() != filter {
+            for (id, plan) in listed {
+                // Filter by label if specified along with a status
+                if let Some(label) = &label {
+                    if !plan.labels().any(|l| l == label) {
                         continue;
                     }
                 }
@@ -325,27 +860,144 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                 };
 
                 let task_count = plan.tasks().len();
-                let done = plan.tasks().iter().filter(|t| t.is_done()).count();
+                // Listing reads from the cache only, so tasks done by merging their
+                // patch aren't counted here; `show` counts them.
+                let done = plan.tasks().iter().filter(|t| plan.is_task_done(&t.id)).count();
 
                 println!("{} {} {} [{}/{}]", status_icon, short_id(&id), plan.title(), done, task_count);
             }
         }
-        Commands::Show { id, json } => {
+        Commands::Mine => {
             let plans = Plans::open(&repo)?;
-            let plan_id = resolve_cob_prefix(&id, &TYPENAME, &repo)?;
+            let did = profile.did();
 
-            let Some(plan) = plans.get(&plan_id)? else {
-                return Err(format!("Plan not found: {id}").into());
-            };
+            let mut any = false;
+            for (id, plan) in plans.find_by_assignee(&did)? {
+                if matches!(plan.status(), PlanStatus::Archived) {
+                    continue;
+                }
+                let tasks = plan
+                    .unblocked_tasks_in(&repo)?
+                    .filter(|t| t.is_assigned_to(&did))
+                    .collect::<Vec<_>>();
+                if tasks.is_empty() {
+                    continue;
+                }
+                if any {
+                    println!();
+                }
+                any = true;
 
-            if json {
+                println!("{} ({})", plan.title(), short_id(&id));
+                for task in tasks {
+                    let estimate = task.estimate.as_ref().map(|e| format!(" ({})", e)).unwrap_or_default();
+                    println!("  [ ] {} ({}){}", task.subject, short_id(&task.id.into()), estimate);
+                }
+            }
+            if !any {
+                println!("No unblocked tasks are assigned to you");
+            }
+        }
+        Commands::Log { id, json } => {
+            let plans = Plans::open(&repo)?;
+            let plan_id = resolve_cob_prefix(&id, &TYPENAME, &repo)?;
+            let history = plans.history(&plan_id)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&history)?);
+            } else {
+                // Task subjects as of their latest edit, for describing task actions.
+                let mut subjects = std::collections::BTreeMap::new();
+                for event in &history {
+                    match (&event.action, event.task) {
+                        (Action::AddTask { subject, .. }, Some(task)) => {
+                            subjects.insert(task, subject.clone());
+                        }
+                        (Action::EditTask { task_id, subject: Some(subject), .. }, _) => {
+                            subjects.insert(*task_id, subject.clone());
+                        }
+                        _ => {}
+                    }
+                }
+
+                for event in &history {
+                    println!(
+                        "{} {} {}  {}",
+                        short_id(&event.entry.into()),
+                        format_timestamp(event.timestamp),
+                        short_did(&event.author),
+                        describe_action(&event.action, &subjects)
+                    );
+                }
+            }
+        }
+        Commands::Diff { id, from, to, json } => {
+            let plans = Plans::open(&repo)?;
+            let plan_id = resolve_cob_prefix(&id, &TYPENAME, &repo)?;
+            let history = plans.history(&plan_id)?;
+
+            let revision = |rev: Option<String>| -> Result<Option<Plan>, Box<dyn std::error::Error>> {
+                match rev {
+                    Some(rev) => {
+                        let entry = resolve_entry_prefix(&rev, &history)?;
+                        Ok(plans.get_at(&plan_id, &entry)?)
+                    }
+                    None => Ok(None),
+                }
+            };
+            let old = match revision(from)? {
+                Some(plan) => plan,
+                None => {
+                    // The root change is always evaluated first.
+                    let root = history.first().map(|e| e.entry).ok_or_else(|| format!("Plan not found: {id}"))?;
+                    plans.get_at(&plan_id, &root)?.ok_or_else(|| format!("Plan not found: {id}"))?
+                }
+            };
+            let new = match revision(to)? {
+                Some(plan) => plan,
+                None => plans.get(&plan_id)?.ok_or_else(|| format!("Plan not found: {id}"))?,
+            };
+            let diff = PlanDiff::new(&old, &new);
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else if diff.is_empty() {
+                println!("No changes");
+            } else {
+                print_diff(&diff, &old, &new);
+            }
+        }
+        Commands::Show { id, json, critical_path } => {
+            let plans = Plans::open(&repo)?;
+            let plan_id = resolve_cob_prefix(&id, &TYPENAME, &repo)?;
+
+            let Some(plan) = plans.get(&plan_id)? else {
+                return Err(format!("Plan not found: {id}").into());
+            };
+
+            if json {
                 println!("{}", serde_json::to_string_pretty(&plan)?);
             } else {
                 println!("# {}", plan.title());
                 println!();
                 println!("ID: {}", plan_id);
-                println!("Status: {:?}", plan.status());
+                let effective = plan.effective_status_in(&repo)?;
+                if effective != *plan.status() {
+                    println!("Status: {:?} (tasks imply {:?}; run `rad-plan status <id> --auto`)", plan.status(), effective);
+                } else {
+                    println!("Status: {:?}", plan.status());
+                }
                 println!("Author: {}", plan.author());
+
+                let doc = repo.identity_doc()?;
+                if plan.approvals().next().is_some() || *plan.status() == PlanStatus::Draft {
+                    println!("Approvals: {}/{}", plan.approvers(&doc).count(), doc.threshold());
+                    let revision = plan.revision();
+                    for (did, approval) in plan.approvals() {
+                        let stale = if approval.revision == revision { "" } else { " (stale)" };
+                        println!("  - {}{}", short_did(did), stale);
+                    }
+                }
                 println!();
 
                 if !plan.description().is_empty() {
@@ -357,19 +1009,40 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
 
                 println!("## Tasks ({} total)", plan.tasks().len());
                 println!();
+                if let Some(totals) = estimate_totals(&plan) {
+                    println!("Estimate: {totals}");
+                    println!();
+                }
 
-                for task in plan.tasks() {
-                    let checkbox = if task.is_done() { "[x]" } else { "[ ]" };
-                    let estimate = task.estimate.as_ref().map(|e| format!(" ({})", e)).unwrap_or_default();
-                    let commit_info = task.linked_commit.as_ref().map(|c| {
-                        let s = c.to_string();
-                        format!(" -> {}", &s[..7.min(s.len())])
-                    }).unwrap_or_default();
-                    println!("{} {}{}{}", checkbox, task.subject, estimate, commit_info);
-
-                    if let Some(desc) = &task.description {
-                        if !desc.is_empty() {
-                            println!("    {}", desc);
+                // Tasks are grouped by milestone, those without one first.
+                let today = DueDate::today();
+                let groups = std::iter::once(None).chain(plan.milestones().iter().map(Some));
+                let mut listed = false;
+                for milestone in groups {
+                    if let Some(milestone) = milestone {
+                        if listed {
+                            println!();
+                        }
+                        println!("### {}", describe_milestone(&plan, milestone, today));
+                        println!();
+                        listed = true;
+                    }
+                    for (depth, task) in plan.milestone_task_tree(milestone.map(|m| &m.id)) {
+                        listed = true;
+                        let indent = "  ".repeat(depth);
+                        let checkbox = if plan.is_task_done_in(&task.id, &repo)? { "[x]" } else { "[ ]" };
+                        let estimate = task.estimate.as_ref().map(|e| format!(" ({})", e)).unwrap_or_default();
+                        let commit_info = task.linked_commit.as_ref().map(|c| {
+                            let s = c.to_string();
+                            format!(" -> {}", &s[..7.min(s.len())])
+                        }).unwrap_or_default();
+                        let patch_info = task.linked_patch.as_ref().map(|p| format!(" (patch {})", short_id(p))).unwrap_or_default();
+                        println!("{}{} {}{}{}{}", indent, checkbox, task.subject, estimate, commit_info, patch_info);
+
+                        if let Some(desc) = &task.description {
+                            if !desc.is_empty() {
+                                println!("{}    {}", indent, desc);
+                            }
                         }
                     }
                 }
@@ -391,33 +1064,106 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                         println!("  - {}", patch_id);
                     }
                 }
+
+                if critical_path {
+                    let path = plan.critical_path();
+                    let schedule = plan.schedule();
+                    let remaining = schedule.iter().map(|s| s.earliest_finish).max().unwrap_or(0);
+
+                    println!();
+                    println!("## Critical Path ({} remaining)", Estimate::from_minutes(remaining));
+                    println!();
+                    for task in path {
+                        let mark = if plan.is_task_done_in(&task.id, &repo)? { "[x]" } else { "[ ]" };
+                        println!("{} {} ({})", mark, task.subject, short_id(&task.id.into()));
+                    }
+                }
+
+                warn_dependency_errors(&plan);
             }
         }
-        Commands::Status { id, status } => {
-            let mut plans = Plans::open(&repo)?;
+        Commands::Status { id, status, auto, force } => {
+            let mut plans = plans_mut(&profile, &repo)?;
             let plan_id = resolve_cob_prefix(&id, &TYPENAME, &repo)?;
-            let new_status = parse_plan_status(&status);
             let signer = profile.signer()?;
 
             let mut plan = plans.get_mut(&plan_id)?;
-            plan.set_status(new_status, &signer)?;
+            if auto {
+                match plan.reconcile_status(&signer)? {
+                    Some(status) => println!("Plan {} status set to: {:?}", short_id(&plan_id), status),
+                    None => println!("Plan {} status is up to date: {:?}", short_id(&plan_id), plan.status()),
+                }
+                return Ok(());
+            }
+            let new_status = parse_plan_status(status.as_deref().unwrap_or_default());
+            if force {
+                plan.force_status(new_status, &signer)?;
+            } else {
+                plan.set_status(new_status, &signer)?;
+            }
 
             println!("Plan {} status set to: {:?}", short_id(&plan_id), new_status);
         }
+        Commands::Approve { id, revoke } => {
+            let mut plans = plans_mut(&profile, &repo)?;
+            let plan_id = resolve_cob_prefix(&id, &TYPENAME, &repo)?;
+            let signer = profile.signer()?;
+
+            let mut plan = plans.get_mut(&plan_id)?;
+            if revoke {
+                plan.unapprove(&signer)?;
+                println!("Approval of plan {} withdrawn", short_id(&plan_id));
+                return Ok(());
+            }
+            plan.approve(&signer)?;
+
+            let doc = repo.identity_doc()?;
+            println!(
+                "Plan {} approved ({}/{} delegate approvals), status: {:?}",
+                short_id(&plan_id),
+                plan.approvers(&doc).count(),
+                doc.threshold(),
+                plan.status()
+            );
+        }
         Commands::Task { command } => match command {
-            TaskCommands::Add { plan_id, subject, description, estimate, files } => {
-                let mut plans = Plans::open(&repo)?;
+            TaskCommands::Add { plan_id, subjects, description, estimate, files, parent, milestone } => {
+                let mut plans = plans_mut(&profile, &repo)?;
                 let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
                 let signer = profile.signer()?;
 
                 let mut plan = plans.get_mut(&pid)?;
-                plan.add_task(&subject, description, estimate, files, &signer)?;
+                let parent = parent.map(|p| resolve_task_prefix(&p, &plan)).transpose()?;
+                let milestone = milestone.map(|m| resolve_milestone(&m, &plan)).transpose()?;
+                let message = if subjects.len() == 1 { "Add task" } else { "Add tasks" };
+                let (_, task_ids) = plan.batch(message, &signer, |b| {
+                    for subject in &subjects {
+                        b.add_task(subject, description.clone(), estimate.clone(), files.clone());
+                    }
+                })?;
+                // Task IDs derive from the change that adds them, so parents and milestones
+                // are set after it.
+                if parent.is_some() || milestone.is_some() {
+                    plan.batch("Arrange tasks", &signer, |b| {
+                        for task_id in &task_ids {
+                            if let Some(parent) = parent {
+                                b.set_task_parent(*task_id, Some(parent));
+                            }
+                            if let Some(milestone) = milestone {
+                                b.set_task_milestone(*task_id, Some(milestone));
+                            }
+                        }
+                    })?;
+                }
 
-                println!("Task added to plan {}: {}", short_id(&pid), subject);
+                for (task_id, subject) in task_ids.iter().zip(&subjects) {
+                    println!("Task added to plan {}: {} {}", short_id(&pid), task_id, subject);
+                }
             }
-            TaskCommands::List { plan_id } => {
+            TaskCommands::List { plan_id, assignee } => {
                 let plans = Plans::open(&repo)?;
                 let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let assignee = assignee.map(|a| parse_did(&a, &profile)).transpose()?;
 
                 let Some(plan) = plans.get(&pid)? else {
                     return Err(format!("Plan not found: {plan_id}").into());
@@ -426,18 +1172,85 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                 println!("Tasks for plan: {}", plan.title());
                 println!();
 
-                for task in plan.tasks() {
-                    let checkbox = if task.is_done() { "[x]" } else { "[ ]" };
+                // Tasks are only nested under their parent when all of them are listed.
+                let tree = match &assignee {
+                    Some(did) => plan.tasks_assigned_to(did).map(|t| (0, t)).collect(),
+                    None => plan.task_tree(),
+                };
+                for (depth, task) in tree {
+                    let checkbox = if plan.is_task_done_in(&task.id, &repo)? { "[x]" } else { "[ ]" };
                     let commit_info = task.linked_commit.as_ref().map(|c| {
                         let s = c.to_string();
                         format!(" -> {}", &s[..7.min(s.len())])
                     }).unwrap_or_default();
+                    let patch_info = task.linked_patch.as_ref().map(|p| format!(" (patch {})", short_id(p))).unwrap_or_default();
+
+                    println!(
+                        "{}{} {} ({}){}{}",
+                        "  ".repeat(depth),
+                        checkbox,
+                        task.subject,
+                        short_id(&task.id.into()),
+                        commit_info,
+                        patch_info
+                    );
+                }
+
+                warn_dependency_errors(&plan);
+            }
+            TaskCommands::Graph { plan_id } => {
+                let plans = Plans::open(&repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
 
-                    println!("{} {} ({}){}", checkbox, task.subject, short_id(&task.id.into()), commit_info);
+                let Some(plan) = plans.get(&pid)? else {
+                    return Err(format!("Plan not found: {plan_id}").into());
+                };
+
+                println!("Task graph for plan: {}", plan.title());
+                println!();
+
+                let critical_path = plan
+                    .critical_path()
+                    .iter()
+                    .map(|t| t.id)
+                    .collect::<BTreeSet<_>>();
+
+                for entry in plan.schedule() {
+                    let Some(task) = plan.task(&entry.id) else {
+                        continue;
+                    };
+                    let checkbox = if plan.is_task_done_in(&task.id, &repo)? { "[x]" } else { "[ ]" };
+                    let critical = if critical_path.contains(&task.id) { " *" } else { "" };
+                    let blockers = task
+                        .blocked_by
+                        .iter()
+                        .map(|b| short_id(&(*b).into()))
+                        .collect::<Vec<_>>();
+                    let blockers = if blockers.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" after {}", blockers.join(", "))
+                    };
+
+                    println!(
+                        "{}{} {} ({}) [{}, slack {}]{}{}",
+                        "  ".repeat(entry.depth),
+                        checkbox,
+                        task.subject,
+                        short_id(&task.id.into()),
+                        Estimate::from_minutes(entry.duration),
+                        Estimate::from_minutes(entry.slack),
+                        blockers,
+                        critical,
+                    );
                 }
+                println!();
+                println!("* on the critical path");
+
+                warn_dependency_errors(&plan);
             }
             TaskCommands::LinkCommit { plan_id, task_id, commit } => {
-                let mut plans = Plans::open(&repo)?;
+                let mut plans = plans_mut(&profile, &repo)?;
                 let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
                 let oid = resolve_commit_sha(&commit, &repo)?;
                 let signer = profile.signer()?;
@@ -452,7 +1265,7 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                 println!("Task {} linked to commit {}", short_id(&tid.into()), short_id(&oid.into()));
             }
             TaskCommands::Edit { plan_id, task_id, subject, description, estimate, files } => {
-                let mut plans = Plans::open(&repo)?;
+                let mut plans = plans_mut(&profile, &repo)?;
                 let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
                 let signer = profile.signer()?;
 
@@ -475,7 +1288,7 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                 println!("Task {} updated", short_id(&tid.into()));
             }
             TaskCommands::Remove { plan_id, task_id } => {
-                let mut plans = Plans::open(&repo)?;
+                let mut plans = plans_mut(&profile, &repo)?;
                 let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
                 let signer = profile.signer()?;
 
@@ -488,11 +1301,11 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
 
                 println!("Task {} removed", short_id(&tid.into()));
             }
-            TaskCommands::Link { plan_id, task_id, issue } => {
+            TaskCommands::Link { plan_id, task_id, issue, patch } => {
                 let issue_type: TypeName = "xyz.radicle.issue".parse().unwrap();
-                let mut plans = Plans::open(&repo)?;
+                let patch_type: TypeName = "xyz.radicle.patch".parse().unwrap();
+                let mut plans = plans_mut(&profile, &repo)?;
                 let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
-                let issue_id = resolve_cob_prefix(&issue, &issue_type, &repo)?;
                 let signer = profile.signer()?;
 
                 let plan_ref = plans.get(&pid)?.ok_or_else(|| format!("Plan not found: {plan_id}"))?;
@@ -500,15 +1313,301 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                 drop(plan_ref);
 
                 let mut plan = plans.get_mut(&pid)?;
-                plan.link_task_to_issue(tid, issue_id, &signer)?;
 
-                println!("Task {} linked to issue {}", short_id(&tid.into()), short_id(&issue_id));
+                if let Some(i) = issue {
+                    let issue_id = resolve_cob_prefix(&i, &issue_type, &repo)?;
+                    plan.link_task_to_issue(tid, issue_id, &signer)?;
+                    println!("Task {} linked to issue {}", short_id(&tid.into()), short_id(&issue_id));
+                }
+                if let Some(p) = patch {
+                    let patch_id = resolve_cob_prefix(&p, &patch_type, &repo)?;
+                    plan.link_task_to_patch(tid, patch_id, &signer)?;
+                    println!("Task {} linked to patch {}", short_id(&tid.into()), short_id(&patch_id));
+                }
+            }
+            TaskCommands::Reorder { plan_id, task_ids } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let plan_ref = plans.get(&pid)?.ok_or_else(|| format!("Plan not found: {plan_id}"))?;
+                let tids = task_ids
+                    .iter()
+                    .map(|t| resolve_task_prefix(t, &plan_ref))
+                    .collect::<Result<Vec<_>, _>>()?;
+                drop(plan_ref);
+
+                let mut plan = plans.get_mut(&pid)?;
+                plan.reorder_tasks(tids, &signer)?;
+
+                println!("Tasks reordered in plan {}", short_id(&pid));
+            }
+            TaskCommands::Block { plan_id, task_id, blocked_by } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let plan_ref = plans.get(&pid)?.ok_or_else(|| format!("Plan not found: {plan_id}"))?;
+                let tid = resolve_task_prefix(&task_id, &plan_ref)?;
+                let task = plan_ref.task(&tid).ok_or_else(|| format!("Task not found: {task_id}"))?;
+                let mut blockers = task.blocked_by.clone();
+                for b in &blocked_by {
+                    blockers.push(resolve_task_prefix(b, &plan_ref)?);
+                }
+
+                let (blockers, errors) = plan_ref.validate_blockers(&tid, blockers);
+                if let Some(error) = errors.first() {
+                    return Err(format!(
+                        "Cannot block task {} on {}: {}",
+                        short_id(&tid.into()),
+                        short_id(&error.blocker.into()),
+                        error.kind
+                    )
+                    .into());
+                }
+                drop(plan_ref);
+
+                let mut plan = plans.get_mut(&pid)?;
+                plan.set_task_blocked_by(tid, blockers, &signer)?;
+
+                println!("Task {} blockers updated", short_id(&tid.into()));
+            }
+            TaskCommands::Unblock { plan_id, task_id, blocked_by } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let plan_ref = plans.get(&pid)?.ok_or_else(|| format!("Plan not found: {plan_id}"))?;
+                let tid = resolve_task_prefix(&task_id, &plan_ref)?;
+                let task = plan_ref.task(&tid).ok_or_else(|| format!("Task not found: {task_id}"))?;
+                let remove = blocked_by
+                    .iter()
+                    .map(|b| resolve_task_prefix(b, &plan_ref))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let blockers = if remove.is_empty() {
+                    Vec::new()
+                } else {
+                    task.blocked_by.iter().filter(|b| !remove.contains(b)).copied().collect()
+                };
+                drop(plan_ref);
+
+                let mut plan = plans.get_mut(&pid)?;
+                plan.set_task_blocked_by(tid, blockers, &signer)?;
+
+                println!("Task {} blockers updated", short_id(&tid.into()));
+            }
+            TaskCommands::Parent { plan_id, task_id, parent } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let mut plan = plans.get_mut(&pid)?;
+                let tid = resolve_task_prefix(&task_id, &plan)?;
+                let parent = parent.map(|p| resolve_task_prefix(&p, &plan)).transpose()?;
+                if let Some(parent) = parent {
+                    if parent == tid || plan.is_ancestor(&tid, &parent) {
+                        return Err(format!(
+                            "Cannot make task {} a subtask of {}: it would be its own ancestor",
+                            short_id(&tid.into()),
+                            short_id(&parent.into())
+                        )
+                        .into());
+                    }
+                }
+                plan.set_task_parent(tid, parent, &signer)?;
+
+                match parent {
+                    Some(parent) => println!(
+                        "Task {} is now a subtask of {}",
+                        short_id(&tid.into()),
+                        short_id(&parent.into())
+                    ),
+                    None => println!("Task {} is now a top-level task", short_id(&tid.into())),
+                }
+            }
+            TaskCommands::Assign { plan_id, task_id, add, delete } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let add = add.iter().map(|d| parse_did(d, &profile)).collect::<Result<Vec<_>, _>>()?;
+                let delete = delete.iter().map(|d| parse_did(d, &profile)).collect::<Result<Vec<_>, _>>()?;
+                if add.is_empty() && delete.is_empty() {
+                    return Err("Nothing to change: give a DID to --add or --delete".into());
+                }
+
+                let mut plan = plans.get_mut(&pid)?;
+                let tid = resolve_task_prefix(&task_id, &plan)?;
+                plan.assign_task(tid, add, delete, &signer)?;
+
+                let assignees = plan
+                    .task(&tid)
+                    .map(|t| t.assignees.iter().map(|d| d.to_string()).collect::<Vec<_>>())
+                    .unwrap_or_default();
+                println!("Task {} assignees: {}", short_id(&tid.into()), assignees.join(", "));
+            }
+            TaskCommands::Milestone { plan_id, task_id, milestone } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let mut plan = plans.get_mut(&pid)?;
+                let tid = resolve_task_prefix(&task_id, &plan)?;
+                let milestone = milestone.map(|m| resolve_milestone(&m, &plan)).transpose()?;
+                plan.set_task_milestone(tid, milestone, &signer)?;
+
+                match milestone.and_then(|m| plan.milestone(&m)) {
+                    Some(m) => println!("Task {} added to milestone '{}'", short_id(&tid.into()), m.name),
+                    None => println!("Task {} removed from its milestone", short_id(&tid.into())),
+                }
+            }
+        },
+        Commands::Milestone { command } => match command {
+            MilestoneCommands::Add { plan_id, name, due } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let mut plan = plans.get_mut(&pid)?;
+                let milestone_id = plan.add_milestone(&name, due, &signer)?;
+
+                println!("Milestone added to plan {}: {} {}", short_id(&pid), milestone_id, name);
+            }
+            MilestoneCommands::Edit { plan_id, milestone, name, due, no_due } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let mut plan = plans.get_mut(&pid)?;
+                let mid = resolve_milestone(&milestone, &plan)?;
+                let due = if no_due { Some(None) } else { due.map(Some) };
+                if name.is_none() && due.is_none() {
+                    return Err("Nothing to edit: give a new --name, --due or --no-due".into());
+                }
+                plan.edit_milestone(mid, name, due, &signer)?;
+
+                println!("Milestone {} updated", short_id(&mid.into()));
+            }
+            MilestoneCommands::Remove { plan_id, milestone } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let mut plan = plans.get_mut(&pid)?;
+                let mid = resolve_milestone(&milestone, &plan)?;
+                plan.remove_milestone(mid, &signer)?;
+
+                println!("Milestone {} removed from plan {}", short_id(&mid.into()), short_id(&pid));
+            }
+            MilestoneCommands::Reorder { plan_id, milestones } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let mut plan = plans.get_mut(&pid)?;
+                let milestone_ids = milestones
+                    .iter()
+                    .map(|m| resolve_milestone(m, &plan))
+                    .collect::<Result<Vec<_>, _>>()?;
+                plan.reorder_milestones(milestone_ids, &signer)?;
+
+                println!("Milestones of plan {} reordered", short_id(&pid));
+            }
+            MilestoneCommands::List { plan_id } => {
+                let plans = Plans::open(&repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+
+                let Some(plan) = plans.get(&pid)? else {
+                    return Err(format!("Plan not found: {plan_id}").into());
+                };
+
+                println!("Milestones for plan: {}", plan.title());
+                println!();
+
+                let today = DueDate::today();
+                for milestone in plan.milestones() {
+                    let mut done = 0;
+                    for task in plan.milestone_tasks(Some(&milestone.id)) {
+                        if plan.is_task_done_in(&task.id, &repo)? {
+                            done += 1;
+                        }
+                    }
+                    println!(
+                        "{} ({}) [{}/{} tasks]",
+                        describe_milestone(&plan, milestone, today),
+                        short_id(&milestone.id.into()),
+                        done,
+                        plan.milestone_tasks(Some(&milestone.id)).count()
+                    );
+                }
+            }
+        },
+        Commands::File { command } => match command {
+            FileCommands::Add { plan_id, path } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let mut plan = plans.get_mut(&pid)?;
+                plan.add_critical_file(&path, &signer)?;
+
+                println!("Critical file {} added to plan {}", path, short_id(&pid));
+            }
+            FileCommands::Remove { plan_id, path } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let mut plan = plans.get_mut(&pid)?;
+                plan.remove_critical_file(&path, &signer)?;
+
+                println!("Critical file {} removed from plan {}", path, short_id(&pid));
             }
         },
+        Commands::Label { plan_id, add, delete } => {
+            let mut plans = plans_mut(&profile, &repo)?;
+            let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+            let signer = profile.signer()?;
+
+            let add = add.iter().map(|l| Label::from_str(l)).collect::<Result<Vec<_>, _>>()?;
+            let delete = delete.iter().map(|l| Label::from_str(l)).collect::<Result<Vec<_>, _>>()?;
+
+            let mut plan = plans.get_mut(&pid)?;
+            let labels = plan
+                .labels()
+                .filter(|l| !delete.contains(l))
+                .cloned()
+                .chain(add)
+                .collect::<Vec<_>>();
+            plan.label(labels, &signer)?;
+
+            let labels = plan.labels().map(|l| l.to_string()).collect::<Vec<_>>();
+            println!("Plan {} labels: {}", short_id(&pid), labels.join(", "));
+        }
+        Commands::Assign { plan_id, add, delete } => {
+            let mut plans = plans_mut(&profile, &repo)?;
+            let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+            let signer = profile.signer()?;
+
+            let add = add.iter().map(|d| Did::from_str(d)).collect::<Result<Vec<_>, _>>()?;
+            let delete = delete.iter().map(|d| Did::from_str(d)).collect::<Result<Vec<_>, _>>()?;
+
+            let mut plan = plans.get_mut(&pid)?;
+            let assignees = plan
+                .assignees()
+                .filter(|d| !delete.contains(d))
+                .copied()
+                .chain(add)
+                .collect::<Vec<_>>();
+            plan.assign(assignees, &signer)?;
+
+            let assignees = plan.assignees().map(|d| d.to_string()).collect::<Vec<_>>();
+            println!("Plan {} assignees: {}", short_id(&pid), assignees.join(", "));
+        }
         Commands::Link { plan_id, issue, patch } => {
             let issue_type: TypeName = "xyz.radicle.issue".parse().unwrap();
             let patch_type: TypeName = "xyz.radicle.patch".parse().unwrap();
-            let mut plans = Plans::open(&repo)?;
+            let mut plans = plans_mut(&profile, &repo)?;
             let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
             let signer = profile.signer()?;
 
@@ -528,7 +1627,7 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         Commands::Unlink { plan_id, issue, patch } => {
             let issue_type: TypeName = "xyz.radicle.issue".parse().unwrap();
             let patch_type: TypeName = "xyz.radicle.patch".parse().unwrap();
-            let mut plans = Plans::open(&repo)?;
+            let mut plans = plans_mut(&profile, &repo)?;
             let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
             let signer = profile.signer()?;
 
@@ -545,8 +1644,40 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                 println!("Unlinked patch {} from plan {}", short_id(&patch_id), short_id(&pid));
             }
         }
-        Commands::Comment { plan_id, message, reply_to } => {
-            let mut plans = Plans::open(&repo)?;
+        Commands::Comment { command: Some(command), .. } => match command {
+            CommentCommands::Edit { plan_id, comment_id, message } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let plan_ref = plans.get(&pid)?.ok_or_else(|| format!("Plan not found: {plan_id}"))?;
+                let cid = resolve_comment_prefix(&comment_id, &plan_ref)?;
+                drop(plan_ref);
+
+                let mut plan = plans.get_mut(&pid)?;
+                plan.edit_comment(cid, &message, vec![], &signer)?;
+
+                println!("Comment {} edited", short_id(&cid.into()));
+            }
+            CommentCommands::Redact { plan_id, comment_id } => {
+                let mut plans = plans_mut(&profile, &repo)?;
+                let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+                let signer = profile.signer()?;
+
+                let plan_ref = plans.get(&pid)?.ok_or_else(|| format!("Plan not found: {plan_id}"))?;
+                let cid = resolve_comment_prefix(&comment_id, &plan_ref)?;
+                drop(plan_ref);
+
+                let mut plan = plans.get_mut(&pid)?;
+                plan.redact_comment(cid, &signer)?;
+
+                println!("Comment {} redacted", short_id(&cid.into()));
+            }
+        },
+        Commands::Comment { command: None, plan_id, message, reply_to } => {
+            let plan_id = plan_id.ok_or("missing plan ID")?;
+            let message = message.ok_or("missing comment message")?;
+            let mut plans = plans_mut(&profile, &repo)?;
             let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
             let signer = profile.signer()?;
 
@@ -564,6 +1695,165 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
 
             println!("Comment added to plan {}", short_id(&pid));
         }
+        Commands::Import { file } => {
+            let issue_type: TypeName = "xyz.radicle.issue".parse().unwrap();
+            let patch_type: TypeName = "xyz.radicle.patch".parse().unwrap();
+            let doc = PlanDocument::parse(&std::fs::read_to_string(&file)?)?;
+            let status = doc.metadata("Status").map(PlanStatus::from_str).transpose()?;
+            let mut plans = plans_mut(&profile, &repo)?;
+            let signer = profile.signer()?;
+
+            let mut actions = doc
+                .tasks
+                .iter()
+                .map(|t| Action::AddTask {
+                    subject: t.subject.clone(),
+                    description: t.description.clone(),
+                    estimate: t.estimate.clone(),
+                    affected_files: vec![],
+                })
+                .collect::<Vec<_>>();
+            for milestone in &doc.milestones {
+                actions.push(Action::AddMilestone {
+                    name: milestone.name.clone(),
+                    due: milestone.due,
+                });
+            }
+            for i in &doc.issues {
+                let issue_id = resolve_cob_prefix(i, &issue_type, &repo)?;
+                actions.push(Action::LinkIssue { issue_id });
+            }
+            for p in &doc.patches {
+                let patch_id = resolve_cob_prefix(p, &patch_type, &repo)?;
+                actions.push(Action::LinkPatch { patch_id });
+            }
+
+            let (id, plan) = plans.create_with(doc.title.clone(), doc.description.clone(), vec![], actions, &signer)?;
+            println!("Plan created: {}", id);
+            println!("  Title: {}", plan.title());
+            println!("  Tasks: {}", plan.tasks().len());
+
+            // Task and milestone IDs derive from the root change, so commits, blockers,
+            // parents and milestones can only be set after it.
+            let mut commits = Vec::new();
+            for (i, (task, doc_task)) in plan.tasks().iter().zip(&doc.tasks).enumerate() {
+                // Tasks with subtasks are done once their subtasks are.
+                let parent = doc.tasks.iter().any(|t| t.parent == Some(i + 1));
+                match doc_task.commit {
+                    Some(commit) => commits.push((task.id, commit)),
+                    None if doc_task.done && !parent => {
+                        eprintln!("Warning: task '{}' is checked but has no commit; it was imported as not done", task.subject);
+                    }
+                    None => {}
+                }
+            }
+            let task_ids = plan.tasks().iter().map(|t| t.id).collect::<Vec<_>>();
+            let blockers = doc.blockers(&task_ids);
+            let parents = doc.parents(&task_ids);
+            let milestone_ids = plan.milestones().iter().map(|m| m.id).collect::<Vec<_>>();
+            let milestones = doc.task_milestones(&task_ids, &milestone_ids);
+            let status = status.filter(|s| s != plan.status());
+            let arranged = !commits.is_empty() || !blockers.is_empty() || !parents.is_empty() || !milestones.is_empty();
+
+            if arranged || status.is_some() {
+                let mut plan = plans.get_mut(&id)?;
+                if arranged {
+                    plan.batch("Import task commits and arrangement", &signer, |b| {
+                        for (task, commit) in commits {
+                            b.link_task_to_commit(task, commit);
+                        }
+                        for (task, blocked_by) in blockers {
+                            b.set_task_blocked_by(task, blocked_by);
+                        }
+                        for (task, parent) in parents {
+                            b.set_task_parent(task, Some(parent));
+                        }
+                        for (task, milestone) in milestones {
+                            b.set_task_milestone(task, Some(milestone));
+                        }
+                    })?;
+                }
+                // The status is checked against the plan as of the last change, so it
+                // follows the commits in a change of its own.
+                if let Some(status) = status {
+                    match plan.set_status(status, &signer) {
+                        Ok(_) => println!("  Status: {status}"),
+                        Err(e @ radicle_plan_cob::Error::InvalidTransition { .. }) => {
+                            eprintln!("Warning: status '{status}' was not imported: {e}");
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+            }
+        }
+        Commands::Apply { plan_id, file, dry_run } => {
+            let doc = PlanDocument::parse(&std::fs::read_to_string(&file)?)?;
+            let mut plans = plans_mut(&profile, &repo)?;
+            let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+
+            let plan = plans.get(&pid)?.ok_or_else(|| format!("Plan not found: {plan_id}"))?;
+            let sync = doc.sync(&plan)?;
+
+            if sync.is_empty() {
+                println!("Plan {} already matches {}", short_id(&pid), file.display());
+                return Ok(());
+            }
+            if dry_run {
+                let subjects = plan.tasks().iter().map(|t| (t.id, t.subject.clone())).collect();
+                for action in &sync.actions {
+                    println!("{}", describe_action(action, &subjects));
+                }
+                let describe = |r: &TaskRef| match r {
+                    TaskRef::Existing(id) => describe_task(id, &subjects),
+                    TaskRef::Added(n) => format!("new task #{}", n + 1),
+                };
+                if let Some(order) = &sync.order {
+                    let order = order.iter().map(describe).collect::<Vec<_>>();
+                    println!("Reordered tasks: {}", order.join(", "));
+                }
+                for (task, parent) in &sync.parents {
+                    match parent {
+                        Some(parent) => println!("Made task {} a subtask of {}", describe(task), describe(parent)),
+                        None => println!("Made task {} a top-level task", describe(task)),
+                    }
+                }
+                let describe_ref = |r: &MilestoneRef| match r {
+                    MilestoneRef::Existing(id) => plan
+                        .milestone(id)
+                        .map(|m| format!("'{}'", m.name))
+                        .unwrap_or_else(|| short_id(&(*id).into())),
+                    MilestoneRef::Added(n) => format!("new milestone #{}", n + 1),
+                };
+                if let Some(order) = &sync.milestone_order {
+                    let order = order.iter().map(describe_ref).collect::<Vec<_>>();
+                    println!("Reordered milestones: {}", order.join(", "));
+                }
+                for (task, milestone) in &sync.task_milestones {
+                    match milestone {
+                        Some(milestone) => {
+                            println!("Moved task {} to milestone {}", describe(task), describe_ref(milestone))
+                        }
+                        None => println!("Removed task {} from its milestone", describe(task)),
+                    }
+                }
+                return Ok(());
+            }
+
+            let signer = profile.signer()?;
+            let mut plan = plans.get_mut(&pid)?;
+            let changes = plan.apply_document(&doc, &signer)?;
+
+            println!(
+                "Plan {} updated in {} change(s): {} action(s) applied",
+                short_id(&pid),
+                changes.len(),
+                sync.actions.len()
+                    + usize::from(sync.order.is_some())
+                    + sync.parents.len()
+                    + usize::from(sync.milestone_order.is_some())
+                    + sync.task_milestones.len()
+            );
+        }
         Commands::Export { id, format, output } => {
             let plans = Plans::open(&repo)?;
             let plan_id = resolve_cob_prefix(&id, &TYPENAME, &repo)?;
@@ -573,8 +1863,8 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             };
 
             let content = match format.as_str() {
-                "md" => export_markdown(&plan_id, &plan),
-                "json" => serde_json::to_string_pretty(&plan)?,
+                "md" => export_markdown(&plan_id, &plan, &repo)?,
+                "json" => export_json(&plan)?,
                 _ => return Err(format!("Unknown format: {format}").into()),
             };
 
@@ -586,12 +1876,24 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         Commands::Edit { id, title, description } => {
-            let mut plans = Plans::open(&repo)?;
+            let mut plans = plans_mut(&profile, &repo)?;
             let pid = resolve_cob_prefix(&id, &TYPENAME, &repo)?;
             let signer = profile.signer()?;
 
             let mut plan = plans.get_mut(&pid)?;
 
+            if title.is_none() && description.is_none() {
+                let doc = edit_document(&PlanDocument::from_plan_in(&plan, &repo)?)?
+                    .ok_or("Aborted: the plan has no title")?;
+                let changes = plan.apply_document(&doc, &signer)?;
+
+                if changes.is_empty() {
+                    println!("No changes");
+                } else {
+                    println!("Plan {} updated", short_id(&pid));
+                }
+                return Ok(());
+            }
             if let Some(t) = title {
                 plan.edit_title(&t, &signer)?;
                 println!("Plan title updated to: {}", t);
@@ -601,11 +1903,205 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                 println!("Plan description updated");
             }
         }
+        Commands::SyncCommits { plan_id, revspec } => {
+            let mut plans = plans_mut(&profile, &repo)?;
+            let signer = profile.signer()?;
+            let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+            let revspec = revspec.as_deref().unwrap_or("HEAD");
+
+            let mut plan = plans.get_mut(&pid)?;
+            let trailers = plan.sync_commits(&working, revspec, &signer)?;
+
+            for (oid, trailer) in &trailers.unresolved {
+                eprintln!(
+                    "Warning: commit {} names task '{}', which doesn't match exactly one task of the plan",
+                    short_id(&(*oid).into()),
+                    trailer.task
+                );
+            }
+            if trailers.links.is_empty() {
+                println!("No new task commits found in {revspec}");
+                return Ok(());
+            }
+            for (task_id, commit) in &trailers.links {
+                let subject = plan.task(task_id).map(|t| t.subject.as_str()).unwrap_or_default();
+                println!(
+                    "Task {} ({}) linked to commit {}",
+                    subject,
+                    short_id(&(*task_id).into()),
+                    short_id(&(*commit).into())
+                );
+            }
+            let unpushed = trailers
+                .links
+                .iter()
+                .filter(|(_, commit)| repo.backend.find_commit(radicle::git::raw::Oid::from(*commit)).is_err())
+                .count();
+            if unpushed > 0 {
+                eprintln!(
+                    "Warning: {unpushed} linked commit(s) not in storage yet; push them before running `rad-plan verify`"
+                );
+            }
+        }
+        Commands::Verify { plan_id, json } => {
+            let plans = Plans::open(&repo)?;
+            let pid = resolve_cob_prefix(&plan_id, &TYPENAME, &repo)?;
+            let plan = plans.get(&pid)?.ok_or_else(|| format!("Plan not found: {plan_id}"))?;
+            let report = plan.verify_commits(&repo)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!(
+                    "Commits of plan {} against {} ({}):",
+                    short_id(&pid),
+                    report.branch,
+                    short_id(&report.head.into())
+                );
+                for check in &report.commits {
+                    let status = match check.status {
+                        CommitStatus::Merged => "merged",
+                        CommitStatus::Unmerged => "not merged",
+                        CommitStatus::Missing => "missing",
+                    };
+                    let subject = plan.task(&check.task).map(|t| t.subject.as_str()).unwrap_or_default();
+                    println!(
+                        "  {} {} ({}): {}",
+                        short_id(&check.commit.into()),
+                        subject,
+                        short_id(&check.task.into()),
+                        status
+                    );
+                }
+            }
+
+            let failures = report.failures().count();
+            if failures > 0 {
+                return Err(format!("{failures} linked commit(s) not merged into {}", report.branch).into());
+            }
+        }
+        Commands::Which { target } => {
+            let issue_type: TypeName = "xyz.radicle.issue".parse().unwrap();
+            let patch_type: TypeName = "xyz.radicle.patch".parse().unwrap();
+            let plans = Plans::open(&repo)?;
+            let mut found = Vec::new();
+
+            // IDs are tried as every kind of object they could resolve to.
+            if validate_hex_prefix(&target, "ID").is_ok() {
+                if let Ok(id) = resolve_cob_prefix(&target, &issue_type, &repo) {
+                    found.push((format!("issue {}", short_id(&id)), plans.find_by_issue(&id)?));
+                }
+                if let Ok(id) = resolve_cob_prefix(&target, &patch_type, &repo) {
+                    found.push((format!("patch {}", short_id(&id)), plans.find_by_patch(&id)?));
+                }
+                if let Ok(oid) = resolve_commit_sha(&target, &repo) {
+                    found.push((format!("commit {}", short_id(&oid.into())), plans.find_by_commit(&oid)?));
+                }
+            }
+            if found.iter().all(|(_, matches)| matches.is_empty()) {
+                let path = repo_relative_path(&target, working.workdir());
+                found = vec![(format!("file {path}"), plans.find_by_file(&path)?)];
+            }
+
+            let mut any = false;
+            for (what, matches) in found.into_iter().filter(|(_, m)| !m.is_empty()) {
+                any = true;
+                println!("Plans referencing {what}:");
+                for (id, plan) in matches {
+                    println!("  {} {} [{}]", short_id(&id), plan.title(), plan.status());
+                }
+            }
+            if !any {
+                println!("No plans reference '{target}'");
+            }
+        }
+        Commands::Reconcile { id } => {
+            let mut plans = plans_mut(&profile, &repo)?;
+            let signer = profile.signer()?;
+            let ids = match id {
+                Some(id) => vec![resolve_cob_prefix(&id, &TYPENAME, &repo)?],
+                None => plans.all()?.filter_map(|r| r.ok()).map(|(id, _)| id).collect(),
+            };
+
+            let doc = repo.identity_doc()?;
+            let did = profile.did();
+
+            let mut updated = 0;
+            let mut skipped = 0;
+            for pid in ids {
+                let mut plan = plans.get_mut(&pid)?;
+                let old = *plan.status();
+                // Only the plan author and delegates can change its status; skip the
+                // plans that need reconciling but that we can't update.
+                if plan.author().id() != &did && !doc.is_delegate(&did) {
+                    if plan.effective_status_in(&repo)? != old {
+                        eprintln!(
+                            "Skipped plan {} ({}): only its author or a delegate can change its status",
+                            short_id(&pid),
+                            plan.title()
+                        );
+                        skipped += 1;
+                    }
+                    continue;
+                }
+                match plan.reconcile_status(&signer) {
+                    Ok(Some(status)) => {
+                        println!("Plan {} ({}): {old} -> {status}", short_id(&pid), plan.title());
+                        updated += 1;
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Warning: plan {} was not reconciled: {e}", short_id(&pid)),
+                }
+            }
+            if skipped > 0 {
+                println!("Reconciled {updated} plan(s), skipped {skipped}");
+            } else {
+                println!("Reconciled {updated} plan(s)");
+            }
+        }
+        Commands::Cache { id } => {
+            let mut plans = plans_mut(&profile, &repo)?;
+
+            if let Some(id) = id {
+                let pid = resolve_cob_prefix(&id, &TYPENAME, &repo)?;
+                plans.write(&pid)?;
+                println!("Plan {} cached", short_id(&pid));
+            } else {
+                let count = plans.write_all()?;
+                println!("Cached {count} plan(s)");
+            }
+        }
     }
 
     Ok(())
 }
 
+/// Open the plan cache of the profile, creating it if necessary.
+fn plan_cache(profile: &Profile) -> Result<PlanCache, Box<dyn std::error::Error>> {
+    let dir = profile.home.path().join("cobs");
+    std::fs::create_dir_all(&dir)?;
+
+    Ok(PlanCache::open(dir.join(cache::CACHE_FILE))?)
+}
+
+/// Open the plans of a repository for writing, keeping the plan cache up to date.
+fn plans_mut<'a>(
+    profile: &Profile,
+    repo: &'a Repository,
+) -> Result<PlansMut<'a, Repository>, Box<dyn std::error::Error>> {
+    Ok(PlansMut::new(Plans::open(repo)?, plan_cache(profile)?))
+}
+
+/// Express a path relative to the working copy root, if it exists on disk within it.
+/// Other paths are assumed to already be relative to the repository root.
+fn repo_relative_path(path: &str, workdir: Option<&std::path::Path>) -> String {
+    let relative = workdir
+        .and_then(|root| Some((root.canonicalize().ok()?, std::path::Path::new(path).canonicalize().ok()?)))
+        .and_then(|(root, path)| path.strip_prefix(root).ok().map(|p| p.to_string_lossy().into_owned()));
+
+    relative.unwrap_or_else(|| path.to_string())
+}
+
 /// Validate that a string is a valid hex prefix of at least MIN_PREFIX_LEN chars.
 fn validate_hex_prefix(s: &str, label: &str) -> Result<String, Box<dyn std::error::Error>> {
     let prefix = s.to_lowercase();
@@ -670,12 +2166,7 @@ fn resolve_task_prefix(s: &str, plan: &Plan) -> Result<TaskId, Box<dyn std::erro
 
     let prefix = validate_hex_prefix(s, "task ID")?;
 
-    let matches: Vec<TaskId> = plan
-        .tasks()
-        .iter()
-        .filter(|t| t.id.to_string().starts_with(&prefix))
-        .map(|t| t.id)
-        .collect();
+    let matches: Vec<TaskId> = plan.tasks_by_prefix(&prefix).map(|t| t.id).collect();
 
     match matches.len() {
         0 => Err(format!("No task found matching prefix '{s}'").into()),
@@ -691,6 +2182,43 @@ fn resolve_task_prefix(s: &str, plan: &Plan) -> Result<TaskId, Box<dyn std::erro
     }
 }
 
+/// Resolve a milestone from its exact name, or from a full ID or short prefix.
+fn resolve_milestone(s: &str, plan: &Plan) -> Result<MilestoneId, Box<dyn std::error::Error>> {
+    if let Some(milestone) = plan.milestones().iter().find(|m| m.name == s) {
+        return Ok(milestone.id);
+    }
+    if !s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("No milestone named '{s}'").into());
+    }
+    let prefix = validate_hex_prefix(s, "milestone ID")?;
+
+    let matches: Vec<MilestoneId> = plan.milestones_by_prefix(&prefix).map(|m| m.id).collect();
+
+    match matches.len() {
+        0 => Err(format!("No milestone found matching '{s}'").into()),
+        1 => Ok(matches[0]),
+        n => {
+            let ids: Vec<String> = matches.iter().map(|id| short_id(&(*id).into())).collect();
+            Err(format!(
+                "Ambiguous milestone ID prefix '{s}': {n} milestones match ({})",
+                ids.join(", ")
+            )
+            .into())
+        }
+    }
+}
+
+/// Describe a milestone with its due date and completion, flagging it if it's overdue.
+fn describe_milestone(plan: &Plan, milestone: &Milestone, today: DueDate) -> String {
+    let completion = plan.milestone_completion(&milestone.id);
+    let due = match milestone.due {
+        Some(due) if completion < 100.0 && milestone.is_overdue(today) => format!(", due {due}, overdue"),
+        Some(due) => format!(", due {due}"),
+        None => String::new(),
+    };
+    format!("{} ({:.0}% complete{})", milestone.name, completion, due)
+}
+
 /// Resolve a commit SHA from a full or abbreviated form using git-native prefix resolution.
 fn resolve_commit_sha(s: &str, repo: &Repository) -> Result<radicle::git::Oid, Box<dyn std::error::Error>> {
     use radicle::git::Oid;
@@ -743,6 +2271,399 @@ fn resolve_comment_prefix(s: &str, plan: &Plan) -> Result<CommentId, Box<dyn std
     }
 }
 
+/// Print a warning for each task dependency that was dropped during evaluation.
+fn warn_dependency_errors(plan: &Plan) {
+    for error in plan.dependency_errors() {
+        eprintln!(
+            "warning: ignoring dependency of task {} on {}: {}",
+            short_id(&error.task.into()),
+            short_id(&error.blocker.into()),
+            error.kind
+        );
+    }
+}
+
+/// Summarize a plan's estimates, eg. `3d 2h total, 1d remaining (40% complete)`.
+fn estimate_totals(plan: &Plan) -> Option<String> {
+    let total = plan.total_estimate();
+    if !total.is_parsed() {
+        return None;
+    }
+    let remaining = plan.remaining_estimate();
+    let remaining = if remaining.is_parsed() { remaining } else { Estimate::from_minutes(0) };
+
+    Some(format!(
+        "{} total, {} remaining ({:.0}% complete)",
+        total,
+        remaining,
+        plan.completion_percentage()
+    ))
+}
+
+/// Open `$VISUAL` or `$EDITOR` on a plan document, returning the edited document, or
+/// `None` if its title was left empty. If the edited document can't be parsed, the file
+/// is kept and its path is part of the error.
+fn edit_document(doc: &PlanDocument) -> Result<Option<PlanDocument>, Box<dyn std::error::Error>> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let path = std::env::temp_dir().join(format!("rad-plan-{}.md", std::process::id()));
+    std::fs::write(&path, format!("{EDITOR_HELP}{doc}"))?;
+
+    // Run through the shell, so that editors can be configured with arguments.
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(&path)
+        .status();
+    let content = std::fs::read_to_string(&path);
+
+    let status = status.map_err(|e| format!("Failed to run editor '{editor}': {e}"))?;
+    if !status.success() {
+        std::fs::remove_file(&path).ok();
+        return Err(format!("Editor '{editor}' exited with {status}").into());
+    }
+    let content = content?;
+    let text = match content.trim_start().strip_prefix("<!--") {
+        Some(rest) => rest.split_once("-->").map_or("", |(_, rest)| rest),
+        None => &content,
+    };
+
+    let doc = match PlanDocument::parse(text) {
+        Ok(doc) => Some(doc),
+        Err(DocumentError::MissingTitle) => None,
+        // Keep what was written, rather than losing it to a typo.
+        Err(e) => return Err(format!("{e}; your edits were kept in {}", path.display()).into()),
+    };
+    std::fs::remove_file(&path).ok();
+
+    Ok(doc)
+}
+
+/// Load a plan template by name, from the working copy if there is one, and otherwise
+/// from the repository's default branch.
+fn load_template(
+    name: &str,
+    working: &radicle::git::raw::Repository,
+    repo: &Repository,
+) -> Result<PlanTemplate, Box<dyn std::error::Error>> {
+    let path = std::path::Path::new(template::TEMPLATE_DIR).join(format!("{name}.md"));
+
+    if let Some(workdir) = working.workdir() {
+        if let Ok(text) = std::fs::read_to_string(workdir.join(&path)) {
+            return Ok(PlanTemplate::new(text));
+        }
+    }
+    let (_, head) = repo.head()?;
+    let tree = repo.backend.find_commit(head.into())?.tree()?;
+    if let Ok(entry) = tree.get_path(&path) {
+        let blob = entry.to_object(&repo.backend)?.peel_to_blob()?;
+        return Ok(PlanTemplate::new(String::from_utf8_lossy(blob.content())));
+    }
+
+    let mut available = tree
+        .get_path(std::path::Path::new(template::TEMPLATE_DIR))
+        .and_then(|e| e.to_object(&repo.backend)?.peel_to_tree())
+        .map(|dir| {
+            dir.iter()
+                .filter_map(|e| e.name()?.strip_suffix(".md").map(str::to_string))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    available.sort();
+
+    if available.is_empty() {
+        Err(format!("Template '{name}' not found: there are no templates in {}", template::TEMPLATE_DIR).into())
+    } else {
+        Err(format!("Template '{name}' not found; available templates: {}", available.join(", ")).into())
+    }
+}
+
+/// Resolve a change of a plan from a full ID or short prefix, searching its history.
+fn resolve_entry_prefix(s: &str, history: &[PlanEvent]) -> Result<cob::EntryId, Box<dyn std::error::Error>> {
+    let prefix = validate_hex_prefix(s, "change ID")?;
+    let matches = history
+        .iter()
+        .map(|e| e.entry)
+        .filter(|e| e.to_string().starts_with(&prefix))
+        .collect::<BTreeSet<_>>();
+
+    match matches.len() {
+        0 => Err(format!("No change found matching '{s}'").into()),
+        1 => Ok(*matches.iter().next().expect("one match")),
+        n => Err(format!("Ambiguous change ID '{s}': matches {n} changes").into()),
+    }
+}
+
+/// Print the differences between two revisions of a plan.
+fn print_diff(diff: &PlanDiff, old: &Plan, new: &Plan) {
+    let task = |t: &TaskId| {
+        let subject = new.task(t).or_else(|| old.task(t)).map(|t| t.subject.as_str());
+        format!("'{}' ({})", subject.unwrap_or("?"), short_id(&(*t).into()))
+    };
+
+    if let Some(title) = &diff.title {
+        println!("Title: '{}' -> '{}'", title.old, title.new);
+    }
+    if let Some(status) = &diff.status {
+        println!("Status: {} -> {}", status.old, status.new);
+    }
+    if let Some(lines) = &diff.description {
+        println!("Description:");
+        for line in lines {
+            match line {
+                DiffLine::Unchanged(l) => println!("    {l}"),
+                DiffLine::Added(l) => println!("  + {l}"),
+                DiffLine::Removed(l) => println!("  - {l}"),
+            }
+        }
+    }
+
+    if !diff.tasks_added.is_empty() || !diff.tasks_removed.is_empty() || !diff.tasks_edited.is_empty() {
+        println!("Tasks:");
+    }
+    for t in &diff.tasks_added {
+        println!("  + {}", task(&t.id));
+    }
+    for t in &diff.tasks_removed {
+        println!("  - {}", task(&t.id));
+    }
+    for t in &diff.tasks_edited {
+        let mut changes = Vec::new();
+        if let Some(subject) = &t.subject_change {
+            changes.push(format!("subject was '{}'", subject.old));
+        }
+        if t.description.is_some() {
+            changes.push("description edited".to_string());
+        }
+        if let Some(estimate) = &t.estimate {
+            let show = |e: &Option<Estimate>| e.as_ref().map_or("none".to_string(), |e| e.to_string());
+            changes.push(format!("estimate {} -> {}", show(&estimate.old), show(&estimate.new)));
+        }
+        if !t.affected_files.is_empty() {
+            changes.push(format!("files {}", format_set_change(&t.affected_files, |f| f.clone())));
+        }
+        if !t.blocked_by.is_empty() {
+            changes.push(format!("blockers {}", format_set_change(&t.blocked_by, |b| short_id(&(*b).into()))));
+        }
+        if let Some(parent) = &t.parent {
+            match &parent.new {
+                Some(p) => changes.push(format!("subtask of {}", task(p))),
+                None => changes.push("made top-level".to_string()),
+            }
+        }
+        if !t.assignees.is_empty() {
+            changes.push(format!("assignees {}", format_set_change(&t.assignees, short_did)));
+        }
+        if let Some(milestone) = &t.milestone {
+            match &milestone.new {
+                Some(m) => {
+                    let name = new.milestone(m).or_else(|| old.milestone(m)).map(|m| m.name.as_str());
+                    changes.push(format!("moved to milestone '{}'", name.unwrap_or("?")));
+                }
+                None => changes.push("removed from milestone".to_string()),
+            }
+        }
+        if let Some(issue) = &t.linked_issue {
+            match issue.new {
+                Some(i) => changes.push(format!("linked to issue {}", short_id(&i))),
+                None => changes.push("unlinked from issue".to_string()),
+            }
+        }
+        if let Some(commit) = &t.linked_commit {
+            match commit.new {
+                Some(c) => changes.push(format!("done in {}", short_id(&c.into()))),
+                None => changes.push("no longer done".to_string()),
+            }
+        }
+        if let Some(patch) = &t.linked_patch {
+            match patch.new {
+                Some(p) => changes.push(format!("linked to patch {}", short_id(&p))),
+                None => changes.push("unlinked from patch".to_string()),
+            }
+        }
+        println!("  ~ {}: {}", task(&t.id), changes.join(", "));
+    }
+    if diff.tasks_reordered {
+        println!("  Tasks were reordered");
+    }
+
+    let milestones_changed = !diff.milestones_added.is_empty()
+        || !diff.milestones_removed.is_empty()
+        || !diff.milestones_edited.is_empty();
+    if milestones_changed {
+        println!("Milestones:");
+    }
+    for m in &diff.milestones_added {
+        println!("  + '{}' ({})", m.name, short_id(&m.id.into()));
+    }
+    for m in &diff.milestones_removed {
+        println!("  - '{}' ({})", m.name, short_id(&m.id.into()));
+    }
+    for m in &diff.milestones_edited {
+        let mut changes = Vec::new();
+        if let Some(name) = &m.name_change {
+            changes.push(format!("name was '{}'", name.old));
+        }
+        if let Some(due) = &m.due {
+            let show = |d: &Option<DueDate>| d.map_or("none".to_string(), |d| d.to_string());
+            changes.push(format!("due {} -> {}", show(&due.old), show(&due.new)));
+        }
+        println!("  ~ '{}' ({}): {}", m.name, short_id(&m.id.into()), changes.join(", "));
+    }
+    if diff.milestones_reordered {
+        println!("  Milestones were reordered");
+    }
+
+    let sets = [
+        ("Issues", format_set_change(&diff.issues, short_id)),
+        ("Patches", format_set_change(&diff.patches, short_id)),
+        ("Critical files", format_set_change(&diff.critical_files, |f| f.clone())),
+        ("Labels", format_set_change(&diff.labels, |l| l.name().to_string())),
+        ("Assignees", format_set_change(&diff.assignees, short_did)),
+    ];
+    for (name, change) in sets.into_iter().filter(|(_, c)| !c.is_empty()) {
+        println!("{name}: {change}");
+    }
+}
+
+/// Format the elements added to and removed from a set, eg. `+a +b -c`.
+fn format_set_change<T>(change: &SetChange<T>, show: impl Fn(&T) -> String) -> String {
+    let added = change.added.iter().map(|t| format!("+{}", show(t)));
+    let removed = change.removed.iter().map(|t| format!("-{}", show(t)));
+
+    added.chain(removed).collect::<Vec<_>>().join(" ")
+}
+
+/// Parse a DID, or `me` for the profile's own DID.
+fn parse_did(s: &str, profile: &Profile) -> Result<Did, Box<dyn std::error::Error>> {
+    if s == "me" {
+        return Ok(profile.did());
+    }
+    Ok(Did::from_str(s)?)
+}
+
+/// Get a short form of a DID, eg. `z6MkhaX…doK`.
+fn short_did(did: &Did) -> String {
+    let key = did.as_key().to_string();
+    if key.len() <= 12 {
+        return key;
+    }
+    format!("{}…{}", &key[..7], &key[key.len() - 3..])
+}
+
+/// Format a timestamp as a UTC date and time, eg. `2024-05-01 14:03`.
+fn format_timestamp(timestamp: radicle::cob::common::Timestamp) -> String {
+    let secs = timestamp.as_secs();
+    let (days, rem) = (secs / 86400, secs % 86400);
+
+    // Civil date from days since the Unix epoch (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02} {:02}:{:02}", rem / 3600, rem % 3600 / 60)
+}
+
+/// Describe a task by subject and short ID, or by ID alone if its subject is unknown.
+fn describe_task(id: &TaskId, subjects: &std::collections::BTreeMap<TaskId, String>) -> String {
+    match subjects.get(id) {
+        Some(subject) => format!("'{subject}' ({})", short_id(&(*id).into())),
+        None => short_id(&(*id).into()),
+    }
+}
+
+/// Describe an action in a plan's history.
+fn describe_action(action: &Action, subjects: &std::collections::BTreeMap<TaskId, String>) -> String {
+    let task = |id: &TaskId| describe_task(id, subjects);
+    let ids = |ids: &[TaskId]| ids.iter().map(task).collect::<Vec<_>>().join(", ");
+
+    match action {
+        Action::Open { title, .. } => format!("Opened plan '{title}'"),
+        Action::EditTitle { title } => format!("Changed title to '{title}'"),
+        Action::EditDescription { .. } => "Edited description".to_string(),
+        Action::SetStatus { status, force: false } => format!("Set status to {status}"),
+        Action::SetStatus { status, force: true } => format!("Set status to {status} (override)"),
+        Action::AddTask { subject, estimate, .. } => match estimate {
+            Some(estimate) => format!("Added task '{subject}' ({estimate})"),
+            None => format!("Added task '{subject}'"),
+        },
+        Action::EditTask { task_id, .. } => format!("Edited task {}", task(task_id)),
+        Action::SetTaskStatus { task_id, .. } => format!("Set status of task {}", task(task_id)),
+        Action::LinkTaskToCommit { task_id, commit } => {
+            format!("Linked task {} to commit {}", task(task_id), short_id(&(*commit).into()))
+        }
+        Action::RemoveTask { task_id } => format!("Removed task {}", task(task_id)),
+        Action::ReorderTasks { .. } => "Reordered tasks".to_string(),
+        Action::SetTaskBlockedBy { task_id, blocked_by } if blocked_by.is_empty() => {
+            format!("Unblocked task {}", task(task_id))
+        }
+        Action::SetTaskBlockedBy { task_id, blocked_by } => {
+            format!("Blocked task {} on {}", task(task_id), ids(blocked_by))
+        }
+        Action::SetTaskParent { task_id, parent: Some(parent) } => {
+            format!("Made task {} a subtask of {}", task(task_id), task(parent))
+        }
+        Action::SetTaskParent { task_id, parent: None } => {
+            format!("Made task {} a top-level task", task(task_id))
+        }
+        Action::SetTaskMilestone { task_id, milestone_id: Some(milestone_id) } => {
+            format!("Moved task {} to milestone {}", task(task_id), short_id(&(*milestone_id).into()))
+        }
+        Action::SetTaskMilestone { task_id, milestone_id: None } => {
+            format!("Removed task {} from its milestone", task(task_id))
+        }
+        Action::AddMilestone { name, due: Some(due) } => format!("Added milestone '{name}' (due {due})"),
+        Action::AddMilestone { name, due: None } => format!("Added milestone '{name}'"),
+        Action::EditMilestone { milestone_id, .. } => format!("Edited milestone {}", short_id(&(*milestone_id).into())),
+        Action::RemoveMilestone { milestone_id } => {
+            format!("Removed milestone {}", short_id(&(*milestone_id).into()))
+        }
+        Action::ReorderMilestones { .. } => "Reordered milestones".to_string(),
+        Action::LinkIssue { issue_id } => format!("Linked issue {}", short_id(issue_id)),
+        Action::UnlinkIssue { issue_id } => format!("Unlinked issue {}", short_id(issue_id)),
+        Action::LinkPatch { patch_id } => format!("Linked patch {}", short_id(patch_id)),
+        Action::UnlinkPatch { patch_id } => format!("Unlinked patch {}", short_id(patch_id)),
+        Action::LinkTaskToIssue { task_id, issue_id } => {
+            format!("Linked task {} to issue {}", task(task_id), short_id(issue_id))
+        }
+        Action::LinkTaskToPatch { task_id, patch_id } => {
+            format!("Linked task {} to patch {}", task(task_id), short_id(patch_id))
+        }
+        Action::AssignTask { task_id, add, remove } => {
+            let dids = |dids: &BTreeSet<Did>| dids.iter().map(short_did).collect::<Vec<_>>().join(", ");
+            match (add.is_empty(), remove.is_empty()) {
+                (false, true) => format!("Assigned {} to task {}", dids(add), task(task_id)),
+                (true, false) => format!("Unassigned {} from task {}", dids(remove), task(task_id)),
+                _ => format!("Assigned {} to task {}, unassigning {}", dids(add), task(task_id), dids(remove)),
+            }
+        }
+        Action::AddCriticalFile { path } => format!("Added critical file {path}"),
+        Action::RemoveCriticalFile { path } => format!("Removed critical file {path}"),
+        Action::Comment { reply_to: Some(_), .. } => "Replied to a comment".to_string(),
+        Action::Comment { .. } => "Commented".to_string(),
+        Action::CommentEdit { id, .. } => format!("Edited comment {}", short_id(&(*id).into())),
+        Action::CommentRedact { id } => format!("Redacted comment {}", short_id(&(*id).into())),
+        Action::Label { labels } => {
+            let labels = labels.iter().map(|l| l.name()).collect::<Vec<_>>();
+            format!("Set labels to [{}]", labels.join(", "))
+        }
+        Action::Assign { assignees } => {
+            let assignees = assignees.iter().map(short_did).collect::<Vec<_>>();
+            format!("Set assignees to [{}]", assignees.join(", "))
+        }
+        Action::Approve { revision } => format!("Approved revision {}", short_id(&(*revision).into())),
+        Action::Unapprove => "Withdrew approval".to_string(),
+    }
+}
+
 /// Get a short form of an object ID.
 fn short_id(id: &ObjectId) -> String {
     let s = id.to_string();
@@ -817,50 +2738,45 @@ mod tests {
     }
 }
 
-/// Export a plan as markdown.
-fn export_markdown(id: &PlanId, plan: &radicle_plan_cob::Plan) -> String {
-    let mut out = String::new();
-
-    out.push_str(&format!("# {}\n\n", plan.title()));
-    out.push_str(&format!("**ID:** {}\n", id));
-    out.push_str(&format!("**Status:** {:?}\n", plan.status()));
-    out.push_str(&format!("**Author:** {}\n\n", plan.author()));
-
-    if !plan.description().is_empty() {
-        out.push_str("## Description\n\n");
-        out.push_str(plan.description());
-        out.push_str("\n\n");
-    }
-
-    out.push_str(&format!("## Tasks ({})\n\n", plan.tasks().len()));
-
-    for task in plan.tasks() {
-        let checkbox = if task.is_done() { "[x]" } else { "[ ]" };
-        let estimate = task.estimate.as_ref().map(|e| format!(" _({})", e)).unwrap_or_default();
-        out.push_str(&format!("- {} {}{}\n", checkbox, task.subject, estimate));
-
-        if let Some(desc) = &task.description {
-            if !desc.is_empty() {
-                out.push_str(&format!("  - {}\n", desc));
-            }
-        }
-    }
-
-    let issues: Vec<_> = plan.related_issues().collect();
-    if !issues.is_empty() {
-        out.push_str("\n## Linked Issues\n\n");
-        for issue_id in issues {
-            out.push_str(&format!("- {}\n", issue_id));
-        }
+/// Export a plan as JSON, with estimate totals.
+fn export_json(plan: &Plan) -> Result<String, serde_json::Error> {
+    let mut value = serde_json::to_value(plan)?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert(
+            "totalEstimate".to_string(),
+            serde_json::to_value(plan.total_estimate())?,
+        );
+        object.insert(
+            "remainingEstimate".to_string(),
+            serde_json::to_value(plan.remaining_estimate())?,
+        );
+        object.insert(
+            "completionPercentage".to_string(),
+            serde_json::to_value(plan.completion_percentage())?,
+        );
+        let milestones = plan
+            .milestones()
+            .iter()
+            .map(|m| (m.id.to_string(), plan.milestone_completion(&m.id)))
+            .collect::<BTreeMap<_, _>>();
+        object.insert("milestoneCompletion".to_string(), serde_json::to_value(milestones)?);
     }
+    serde_json::to_string_pretty(&value)
+}
 
-    let patches: Vec<_> = plan.related_patches().collect();
-    if !patches.is_empty() {
-        out.push_str("\n## Linked Patches\n\n");
-        for patch_id in patches {
-            out.push_str(&format!("- {}\n", patch_id));
-        }
+/// Export a plan as markdown.
+fn export_markdown(
+    id: &PlanId,
+    plan: &radicle_plan_cob::Plan,
+    repo: &Repository,
+) -> Result<String, radicle_plan_cob::Error> {
+    let mut doc = PlanDocument::from_plan_in(plan, repo)?;
+
+    doc.metadata.push(("ID".to_string(), id.to_string()));
+    doc.metadata.push(("Status".to_string(), plan.status().to_string()));
+    doc.metadata.push(("Author".to_string(), plan.author().to_string()));
+    if let Some(totals) = estimate_totals(plan) {
+        doc.metadata.push(("Estimate".to_string(), totals));
     }
-
-    out
+    Ok(doc.to_string())
 }
@@ -0,0 +1,209 @@
+//! Plan milestones.
+//!
+//! A milestone groups some of a plan's tasks, eg. into phases or releases, and may
+//! have a due date. Like tasks, milestones are kept in order, and each task belongs
+//! to at most one milestone.
+//!
+//! Due dates are calendar dates, written and stored as `YYYY-MM-DD`.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use radicle::cob::common::Timestamp;
+use radicle::cob::ActorId;
+use radicle::git::Oid;
+use radicle::prelude::Did;
+
+/// Milestone identifier. See [`milestone_id`](crate::milestone_id).
+pub type MilestoneId = Oid;
+
+/// Error parsing a due date.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DueDateError {
+    /// The date isn't written as `YYYY-MM-DD`.
+    #[error("invalid date '{0}': expected YYYY-MM-DD")]
+    Format(String),
+    /// The month or day is out of range.
+    #[error("invalid date '{0}': no such day")]
+    Range(String),
+}
+
+/// A milestone's due date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DueDate {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl DueDate {
+    /// Create a date, if it exists.
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let valid = (1..=9999).contains(&year)
+            && (1..=12).contains(&month)
+            && (1..=days_in_month(year, month)).contains(&day);
+
+        valid.then_some(Self { year, month, day })
+    }
+
+    /// Get today's date, in UTC.
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Self::from_days(secs / 86400)
+    }
+
+    /// Get the date a number of days after 1970-01-01.
+    fn from_days(days: u64) -> Self {
+        // Shift the epoch to 0000-03-01, so that leap days end each 400-year era.
+        let days = days + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+        Self {
+            year: year.min(9999) as u16,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+
+    /// Year.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Month, from 1 to 12.
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Day of the month, starting from 1.
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+/// Number of days in a month.
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl fmt::Display for DueDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for DueDate {
+    type Err = DueDateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let parts = s.split('-').collect::<Vec<_>>();
+        let [year, month, day] = parts.as_slice() else {
+            return Err(DueDateError::Format(s.to_string()));
+        };
+        let number = |part: &str, len: usize| {
+            (part.len() == len && part.bytes().all(|b| b.is_ascii_digit()))
+                .then(|| part.parse::<u16>().ok())
+                .flatten()
+                .ok_or_else(|| DueDateError::Format(s.to_string()))
+        };
+        let (year, month, day) = (number(*year, 4)?, number(*month, 2)?, number(*day, 2)?);
+
+        Self::new(year, month as u8, day as u8).ok_or_else(|| DueDateError::Range(s.to_string()))
+    }
+}
+
+impl Serialize for DueDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DueDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A milestone within a plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Milestone {
+    /// Unique identifier.
+    pub id: MilestoneId,
+    /// Milestone name.
+    pub name: String,
+    /// Optional due date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<DueDate>,
+    /// Author who created the milestone.
+    pub author: Did,
+    /// When the milestone was created.
+    pub created_at: Timestamp,
+}
+
+impl Milestone {
+    /// Create a new milestone.
+    pub fn new(id: MilestoneId, name: String, due: Option<DueDate>, author: ActorId, timestamp: Timestamp) -> Self {
+        Self {
+            id,
+            name,
+            due,
+            author: author.into(),
+            created_at: timestamp,
+        }
+    }
+
+    /// Check whether the milestone's due date is before the given date.
+    pub fn is_overdue(&self, today: DueDate) -> bool {
+        self.due.is_some_and(|due| due < today)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due_date_parse() {
+        let date = "2025-02-28".parse::<DueDate>().unwrap();
+        assert_eq!((date.year(), date.month(), date.day()), (2025, 2, 28));
+        assert_eq!(date.to_string(), "2025-02-28");
+
+        assert!("2024-02-29".parse::<DueDate>().is_ok());
+        assert_eq!(
+            "2025-02-29".parse::<DueDate>(),
+            Err(DueDateError::Range("2025-02-29".to_string()))
+        );
+        assert!(matches!("2025-2-28".parse::<DueDate>(), Err(DueDateError::Format(_))));
+        assert!(matches!("tomorrow".parse::<DueDate>(), Err(DueDateError::Format(_))));
+    }
+
+    #[test]
+    fn test_due_date_from_days() {
+        assert_eq!(DueDate::from_days(0).to_string(), "1970-01-01");
+        assert_eq!(DueDate::from_days(11_016).to_string(), "2000-02-29");
+        assert_eq!(DueDate::from_days(20_454).to_string(), "2026-01-01");
+    }
+}
@@ -1,19 +1,94 @@
 //! Plan state structures.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Deref;
 
 use serde::{Deserialize, Serialize};
 
 use radicle::cob::common::{Author, Label, Timestamp};
+use radicle::cob::patch::{self, Patch};
 use radicle::cob::thread::{CommentId, Thread};
-use radicle::cob::{ActorId, ObjectId};
+use radicle::cob::{self, store, ActorId, EntryId, ObjectId};
 use radicle::git::Oid;
-use radicle::prelude::Did;
+use radicle::prelude::{Did, Doc, ReadRepository};
+
+use crate::estimate::Estimate;
+use crate::milestone::{Milestone, MilestoneId};
+use crate::Error;
 
 /// Task identifier (same as entry ID that created it).
 pub type TaskId = Oid;
 
+/// Logical clock of a write, used to order concurrent writes to the same register.
+///
+/// Clocks are ordered by timestamp first, then by skew, then by entry ID, so that
+/// concurrent writes with equal timestamps still resolve the same way on every peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Clock {
+    /// Timestamp of the change that performed the write, or of the latest write it
+    /// observed if that is later.
+    pub(crate) timestamp: Timestamp,
+    /// How many observed writes with the same timestamp precede the write.
+    pub(crate) skew: u32,
+    /// Change that performed the write.
+    pub(crate) entry: EntryId,
+}
+
+impl Clock {
+    /// Clock of a write made by the given change.
+    pub(crate) fn new(timestamp: Timestamp, entry: EntryId) -> Self {
+        Self { timestamp, skew: 0, entry }
+    }
+
+    /// Advance the clock past a write it observed, so that a write always orders
+    /// after the writes it causally follows, whatever their timestamps.
+    pub(crate) fn after(self, prev: Clock) -> Self {
+        if self > prev {
+            return self;
+        }
+        Self {
+            timestamp: prev.timestamp,
+            skew: prev.skew + 1,
+            entry: self.entry,
+        }
+    }
+}
+
+/// A last-writer-wins register within the plan state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Register {
+    Title,
+    Description,
+    Revision,
+    Status,
+    Labels,
+    Assignees,
+    TaskOrder,
+    TaskSubject(TaskId),
+    TaskDescription(TaskId),
+    TaskEstimate(TaskId),
+    TaskAffectedFiles(TaskId),
+    TaskBlockedBy(TaskId),
+    TaskCommit(TaskId),
+    TaskIssue(TaskId),
+    TaskPatch(TaskId),
+    TaskParent(TaskId),
+    TaskMilestone(TaskId),
+    MilestoneOrder,
+    MilestoneName(MilestoneId),
+    MilestoneDue(MilestoneId),
+    Approval(ActorId),
+}
+
+/// An element of one of the plan's add-wins sets.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Element {
+    Issue(ObjectId),
+    Patch(ObjectId),
+    CriticalFile(String),
+    TaskAssignee(TaskId, Did),
+}
+
 /// Plan status.
 #[derive(Debug, Default, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -58,6 +133,107 @@ impl std::str::FromStr for PlanStatus {
     }
 }
 
+impl PlanStatus {
+    /// Check whether the status state machine allows moving from this status to
+    /// another. Setting the current status again is always allowed. Otherwise:
+    ///
+    /// - `Draft` moves to `Approved`;
+    /// - `Approved` moves back to `Draft`, or to `InProgress` or `Completed`;
+    /// - `InProgress` moves to `Completed`, and `Completed` back to `InProgress`;
+    /// - any status moves to `Archived`, and `Archived` back to `Draft`.
+    pub fn can_transition_to(&self, to: PlanStatus) -> bool {
+        use PlanStatus::*;
+
+        *self == to
+            || matches!(
+                (self, to),
+                (Draft, Approved)
+                    | (Approved, Draft | InProgress | Completed)
+                    | (InProgress, Completed)
+                    | (Completed, InProgress)
+                    | (Draft | Approved | InProgress | Completed, Archived)
+                    | (Archived, Draft)
+            )
+    }
+
+    /// Check whether only delegates may move a plan from this status to another:
+    /// approving a plan, and restoring an archived one.
+    pub fn requires_delegate(&self, to: PlanStatus) -> bool {
+        use PlanStatus::*;
+
+        matches!((self, to), (Draft, Approved) | (Archived, Draft))
+    }
+}
+
+/// Why a status transition was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransitionError {
+    /// The state machine doesn't allow the transition.
+    NotAllowed,
+    /// The plan can't be completed while tasks aren't done, without an override.
+    TasksNotDone(usize),
+    /// The plan can't be approved before enough delegates approved it.
+    NotApproved {
+        /// Delegates that approved the current revision.
+        approvals: usize,
+        /// Approvals needed.
+        threshold: usize,
+    },
+}
+
+impl std::fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAllowed => write!(f, "transition not allowed"),
+            Self::TasksNotDone(n) => write!(f, "{n} task(s) not done; completing anyway requires an override"),
+            Self::NotApproved { approvals, threshold } => {
+                write!(f, "approved by {approvals} of the {threshold} delegate(s) needed")
+            }
+        }
+    }
+}
+
+/// Why a task dependency was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DependencyErrorKind {
+    /// The task was set to block itself.
+    SelfReference,
+    /// The blocking task does not exist, or has been removed.
+    MissingTask,
+    /// The dependency would create a cycle.
+    Cycle,
+}
+
+impl std::fmt::Display for DependencyErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SelfReference => write!(f, "task cannot block itself"),
+            Self::MissingTask => write!(f, "blocking task does not exist"),
+            Self::Cycle => write!(f, "dependency would create a cycle"),
+        }
+    }
+}
+
+/// A task dependency that was dropped while evaluating the plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyError {
+    /// The task whose blockers were set.
+    pub task: TaskId,
+    /// The rejected blocker.
+    pub blocker: TaskId,
+    /// Why the blocker was rejected.
+    pub kind: DependencyErrorKind,
+}
+
+impl std::fmt::Display for DependencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "task {} blocked by {}: {}", self.task, self.blocker, self.kind)
+    }
+}
+
 /// A task within a plan.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -69,21 +245,33 @@ pub struct Task {
     /// Optional detailed description.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
-    /// Optional time estimate.
+    /// Optional estimate of the work needed.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub estimate: Option<String>,
+    pub estimate: Option<Estimate>,
     /// Tasks that must be completed before this one.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub blocked_by: Vec<TaskId>,
+    /// Task this is a subtask of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<TaskId>,
+    /// Milestone the task belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub milestone: Option<MilestoneId>,
     /// Files affected by this task.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub affected_files: Vec<String>,
+    /// People responsible for the task.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub assignees: BTreeSet<Did>,
     /// Linked Radicle issue (if task was converted to an issue).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub linked_issue: Option<ObjectId>,
     /// Linked commit OID — when present, the task is considered done.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub linked_commit: Option<Oid>,
+    /// Linked Radicle patch — once merged, the task is considered done.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linked_patch: Option<ObjectId>,
     /// Author who created the task.
     pub author: Did,
     /// When the task was created.
@@ -96,7 +284,7 @@ impl Task {
         id: TaskId,
         subject: String,
         description: Option<String>,
-        estimate: Option<String>,
+        estimate: Option<Estimate>,
         affected_files: Vec<String>,
         author: ActorId,
         timestamp: Timestamp,
@@ -107,23 +295,119 @@ impl Task {
             description,
             estimate,
             blocked_by: Vec::new(),
+            parent: None,
+            milestone: None,
             affected_files,
+            assignees: BTreeSet::new(),
             linked_issue: None,
             linked_commit: None,
+            linked_patch: None,
             author: author.into(),
             created_at: timestamp,
         }
     }
 
+    /// Check if the task is assigned to the given DID.
+    pub fn is_assigned_to(&self, did: &Did) -> bool {
+        self.assignees.contains(did)
+    }
+
     /// Check if the task is blocked.
     pub fn is_blocked(&self) -> bool {
         !self.blocked_by.is_empty()
     }
 
     /// Check if the task is done (has a linked commit).
+    ///
+    /// This doesn't consult the repository, so tasks done by merging their linked
+    /// patch aren't reported as done; see [`Task::is_done_in`]. Nor does it consider
+    /// subtasks; see [`Plan::is_task_done`].
     pub fn is_done(&self) -> bool {
         self.linked_commit.is_some()
     }
+
+    /// Check if the task is done, either because it has a linked commit, or because
+    /// its linked patch has been merged.
+    pub fn is_done_in<R>(&self, repo: &R) -> Result<bool, Error>
+    where
+        R: ReadRepository + cob::Store,
+    {
+        self.is_done_with(|patch| is_merged(patch, repo))
+    }
+
+    /// Check if the task is done, given a way to tell whether its linked patch has
+    /// been merged. The patch is only looked up if the task has no linked commit.
+    fn is_done_with(&self, is_merged: impl FnOnce(&ObjectId) -> Result<bool, Error>) -> Result<bool, Error> {
+        if self.is_done() {
+            return Ok(true);
+        }
+        match &self.linked_patch {
+            Some(patch) => is_merged(patch),
+            None => Ok(false),
+        }
+    }
+}
+
+/// Check whether a patch exists and has been merged.
+fn is_merged<R>(id: &ObjectId, repo: &R) -> Result<bool, Error>
+where
+    R: ReadRepository + cob::Store,
+{
+    let patch = cob::get::<Patch, _>(repo, &patch::TYPENAME, id).map_err(store::Error::from)?;
+
+    Ok(patch.is_some_and(|p| p.object.is_merged()))
+}
+
+/// Order items as a tree, given each item's parent: every item is followed by its
+/// descendants, and paired with its depth. Items whose parent isn't listed are at the
+/// top level, and siblings keep their relative order.
+pub(crate) fn tree_order<T: Copy + PartialEq>(items: &[(T, Option<T>)]) -> Vec<(usize, T)> {
+    let is_root = |parent: &Option<T>| parent.is_none_or(|p| !items.iter().any(|(id, _)| *id == p));
+    let mut order = Vec::with_capacity(items.len());
+    let mut stack = items
+        .iter()
+        .rev()
+        .filter(|(_, parent)| is_root(parent))
+        .map(|(id, _)| (0, *id))
+        .collect::<Vec<_>>();
+
+    while let Some((depth, id)) = stack.pop() {
+        // Parents form no cycles, but guard against them regardless.
+        if order.iter().any(|(_, o)| *o == id) {
+            continue;
+        }
+        order.push((depth, id));
+        stack.extend(
+            items
+                .iter()
+                .rev()
+                .filter(|(_, parent)| *parent == Some(id))
+                .map(|(child, _)| (depth + 1, *child)),
+        );
+    }
+    order
+}
+
+/// Check whether a referenced path is, or contains, the given path.
+fn path_matches(referenced: &str, path: &str) -> bool {
+    fn normalize(p: &str) -> &str {
+        p.trim_start_matches("./").trim_end_matches('/')
+    }
+    let (referenced, path) = (normalize(referenced), normalize(path));
+
+    !referenced.is_empty()
+        && (path == referenced
+            || path.strip_prefix(referenced).is_some_and(|rest| rest.starts_with('/')))
+}
+
+/// A delegate's approval of a plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Approval {
+    /// Revision of the plan that was approved. See [`Plan::revision`].
+    pub revision: EntryId,
+    /// When the plan was approved.
+    pub timestamp: Timestamp,
 }
 
 /// Plan state. Accumulates [`Action`](crate::Action).
@@ -138,6 +422,9 @@ pub struct Plan {
     pub(crate) status: PlanStatus,
     /// Tasks in this plan.
     pub(crate) tasks: Vec<Task>,
+    /// Milestones, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) milestones: Vec<Milestone>,
     /// Related Radicle issues.
     pub(crate) related_issues: BTreeSet<ObjectId>,
     /// Related Radicle patches.
@@ -154,6 +441,21 @@ pub struct Plan {
     pub(crate) author: Author,
     /// When the plan was created.
     pub(crate) created_at: Timestamp,
+    /// Task dependencies that were dropped during evaluation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) dependency_errors: Vec<DependencyError>,
+    /// Last change to the plan's content, if any after the root change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) revision: Option<EntryId>,
+    /// Approvals, by approver.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) approvals: BTreeMap<Did, Approval>,
+    /// Clocks of the writes to each register; the greatest one won.
+    #[serde(skip)]
+    pub(crate) clocks: BTreeMap<Register, BTreeSet<Clock>>,
+    /// Changes that added each set element and have not been observed by a removal.
+    #[serde(skip)]
+    pub(crate) tags: BTreeMap<Element, BTreeSet<EntryId>>,
 }
 
 impl Plan {
@@ -170,6 +472,7 @@ impl Plan {
             description,
             status: PlanStatus::Draft,
             tasks: Vec::new(),
+            milestones: Vec::new(),
             related_issues: BTreeSet::new(),
             related_patches: BTreeSet::new(),
             critical_files: BTreeSet::new(),
@@ -178,6 +481,11 @@ impl Plan {
             thread,
             author,
             created_at: timestamp,
+            dependency_errors: Vec::new(),
+            revision: None,
+            approvals: BTreeMap::new(),
+            clocks: BTreeMap::new(),
+            tags: BTreeMap::new(),
         }
     }
 
@@ -214,6 +522,33 @@ impl Plan {
             .expect("Plan::root: at least one comment is present")
     }
 
+    /// Get the plan's current revision: the last change to its content (see
+    /// [`Action::is_revision`](crate::Action::is_revision)), or the root change.
+    pub fn revision(&self) -> EntryId {
+        self.revision.unwrap_or(*self.root().0)
+    }
+
+    /// Get the plan's approvals, by approver. Approvals of an older revision than
+    /// the current one are stale.
+    pub fn approvals(&self) -> impl Iterator<Item = (&Did, &Approval)> {
+        self.approvals.iter()
+    }
+
+    /// Get the approvers of the current revision who are delegates of the repository.
+    pub fn approvers<'a>(&'a self, doc: &'a Doc) -> impl Iterator<Item = &'a Did> + 'a {
+        let revision = self.revision();
+        self.approvals
+            .iter()
+            .filter(move |(did, a)| a.revision == revision && doc.is_delegate(did))
+            .map(|(did, _)| did)
+    }
+
+    /// Check whether enough delegates approved the current revision to meet the
+    /// repository's threshold.
+    pub fn is_approved(&self, doc: &Doc) -> bool {
+        self.approvers(doc).count() >= doc.threshold()
+    }
+
     /// Get all tasks.
     pub fn tasks(&self) -> &[Task] {
         &self.tasks
@@ -224,21 +559,224 @@ impl Plan {
         self.tasks.iter().find(|t| &t.id == id)
     }
 
+    /// Get the tasks whose ID starts with the given hex prefix, ignoring case.
+    pub fn tasks_by_prefix<'a>(&'a self, prefix: &str) -> impl Iterator<Item = &'a Task> + 'a {
+        let prefix = prefix.to_lowercase();
+        self.tasks.iter().filter(move |t| t.id.to_string().starts_with(&prefix))
+    }
+
+    /// Get the tasks assigned to a DID, in task order.
+    pub fn tasks_assigned_to<'a>(&'a self, did: &'a Did) -> impl Iterator<Item = &'a Task> + 'a {
+        self.tasks.iter().filter(move |t| t.is_assigned_to(did))
+    }
+
+    /// Get the subtasks of a task, in task order.
+    pub fn children<'a>(&'a self, id: &'a TaskId) -> impl Iterator<Item = &'a Task> + 'a {
+        self.tasks.iter().filter(move |t| t.parent.as_ref() == Some(id))
+    }
+
+    /// Get the tasks as a tree: each task is followed by its subtasks, and paired with
+    /// its depth, starting from zero for top-level tasks. Siblings keep their task order.
+    pub fn task_tree(&self) -> Vec<(usize, &Task)> {
+        let items = self.tasks.iter().map(|t| (t.id, t.parent)).collect::<Vec<_>>();
+
+        tree_order(&items)
+            .into_iter()
+            .filter_map(|(depth, id)| self.task(&id).map(|t| (depth, t)))
+            .collect()
+    }
+
+    /// Check if a task is done: it has a linked commit, or it has subtasks and they are
+    /// all done.
+    pub fn is_task_done(&self, id: &TaskId) -> bool {
+        let Some(task) = self.task(id) else {
+            return false;
+        };
+        if task.is_done() {
+            return true;
+        }
+        let mut children = self.children(id).peekable();
+        children.peek().is_some() && children.all(|c| self.is_task_done(&c.id))
+    }
+
+    /// Check if a task is done, counting tasks whose linked patch has been merged as
+    /// done. See [`Plan::is_task_done`] and [`Task::is_done_in`].
+    pub fn is_task_done_in<R>(&self, id: &TaskId, repo: &R) -> Result<bool, Error>
+    where
+        R: ReadRepository + cob::Store,
+    {
+        let Some(task) = self.task(id) else {
+            return Ok(false);
+        };
+        if task.is_done_in(repo)? {
+            return Ok(true);
+        }
+        let mut any = false;
+        for child in self.children(id) {
+            if !self.is_task_done_in(&child.id, repo)? {
+                return Ok(false);
+            }
+            any = true;
+        }
+        Ok(any)
+    }
+
+    /// Check whether a task is an ancestor of another, following subtask parents.
+    pub fn is_ancestor(&self, ancestor: &TaskId, id: &TaskId) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut current = self.task(id).and_then(|t| t.parent);
+
+        while let Some(parent) = current {
+            if &parent == ancestor {
+                return true;
+            }
+            if !visited.insert(parent) {
+                break;
+            }
+            current = self.task(&parent).and_then(|t| t.parent);
+        }
+        false
+    }
+
+    /// Get the milestones, in order.
+    pub fn milestones(&self) -> &[Milestone] {
+        &self.milestones
+    }
+
+    /// Get a milestone by ID.
+    pub fn milestone(&self, id: &MilestoneId) -> Option<&Milestone> {
+        self.milestones.iter().find(|m| &m.id == id)
+    }
+
+    /// Get the milestones whose ID starts with the given hex prefix, ignoring case.
+    pub fn milestones_by_prefix<'a>(&'a self, prefix: &str) -> impl Iterator<Item = &'a Milestone> + 'a {
+        let prefix = prefix.to_lowercase();
+        self.milestones.iter().filter(move |m| m.id.to_string().starts_with(&prefix))
+    }
+
+    /// Get the tasks of a milestone, or the tasks that belong to no milestone if `id`
+    /// is `None`, in task order.
+    pub fn milestone_tasks<'a>(&'a self, id: Option<&'a MilestoneId>) -> impl Iterator<Item = &'a Task> + 'a {
+        self.tasks.iter().filter(move |t| t.milestone.as_ref() == id)
+    }
+
+    /// Get the tasks of a milestone, or those that belong to no milestone if `id` is
+    /// `None`, as a tree: see [`Plan::task_tree`]. Subtasks of a task in another
+    /// milestone are at the top level.
+    pub fn milestone_task_tree(&self, id: Option<&MilestoneId>) -> Vec<(usize, &Task)> {
+        let items = self.milestone_tasks(id).map(|t| (t.id, t.parent)).collect::<Vec<_>>();
+
+        tree_order(&items)
+            .into_iter()
+            .filter_map(|(depth, id)| self.task(&id).map(|t| (depth, t)))
+            .collect()
+    }
+
     /// Get tasks that are not yet done and whose blockers are all done.
+    ///
+    /// This doesn't consult the repository; see [`Plan::unblocked_tasks_in`].
     pub fn unblocked_tasks(&self) -> impl Iterator<Item = &Task> {
         let done_ids: BTreeSet<_> = self
             .tasks
             .iter()
-            .filter(|t| t.is_done())
+            .filter(|t| self.is_task_done(&t.id))
             .map(|t| t.id)
             .collect();
 
+        self.unblocked_by(done_ids)
+    }
+
+    /// Get tasks that are not yet done and whose blockers are all done, counting tasks
+    /// whose linked patch has been merged as done. See [`Plan::unblocked_tasks`].
+    pub fn unblocked_tasks_in<R>(&self, repo: &R) -> Result<impl Iterator<Item = &Task>, Error>
+    where
+        R: ReadRepository + cob::Store,
+    {
+        let mut done_ids = BTreeSet::new();
+        for task in &self.tasks {
+            if self.is_task_done_in(&task.id, repo)? {
+                done_ids.insert(task.id);
+            }
+        }
+        Ok(self.unblocked_by(done_ids))
+    }
+
+    fn unblocked_by(&self, done_ids: BTreeSet<TaskId>) -> impl Iterator<Item = &Task> {
         self.tasks.iter().filter(move |t| {
-            !t.is_done()
+            !done_ids.contains(&t.id)
                 && t.blocked_by.iter().all(|b| done_ids.contains(b))
         })
     }
 
+    /// Get the task dependencies that were dropped while evaluating the plan.
+    ///
+    /// Blockers that refer to the task itself, to a task that does not exist, or
+    /// that would create a dependency cycle are never stored in [`Task::blocked_by`];
+    /// they are reported here instead.
+    pub fn dependency_errors(&self) -> &[DependencyError] {
+        &self.dependency_errors
+    }
+
+    /// Validate a set of blockers for a task against the current dependency graph.
+    ///
+    /// Returns the blockers that can be applied, in their original order and without
+    /// duplicates, along with an error for each blocker that was rejected.
+    pub fn validate_blockers(
+        &self,
+        task_id: &TaskId,
+        blocked_by: impl IntoIterator<Item = TaskId>,
+    ) -> (Vec<TaskId>, Vec<DependencyError>) {
+        let mut valid = Vec::new();
+        let mut errors = Vec::new();
+
+        for blocker in blocked_by {
+            if valid.contains(&blocker) {
+                continue;
+            }
+            let kind = if &blocker == task_id {
+                Some(DependencyErrorKind::SelfReference)
+            } else if self.task(&blocker).is_none() {
+                Some(DependencyErrorKind::MissingTask)
+            } else if self.depends_on(&blocker, task_id) {
+                Some(DependencyErrorKind::Cycle)
+            } else {
+                None
+            };
+
+            match kind {
+                Some(kind) => errors.push(DependencyError {
+                    task: *task_id,
+                    blocker,
+                    kind,
+                }),
+                None => valid.push(blocker),
+            }
+        }
+        (valid, errors)
+    }
+
+    /// Check whether a task transitively depends on another task.
+    fn depends_on(&self, from: &TaskId, target: &TaskId) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut stack = vec![*from];
+
+        while let Some(id) = stack.pop() {
+            // The target's own blockers are being replaced, so they are not followed.
+            if &id == target || !visited.insert(id) {
+                continue;
+            }
+            if let Some(task) = self.task(&id) {
+                for blocker in &task.blocked_by {
+                    if blocker == target {
+                        return true;
+                    }
+                    stack.push(*blocker);
+                }
+            }
+        }
+        false
+    }
+
     /// Get related issues.
     pub fn related_issues(&self) -> impl Iterator<Item = &ObjectId> {
         self.related_issues.iter()
@@ -254,6 +792,30 @@ impl Plan {
         self.critical_files.iter()
     }
 
+    /// Check whether the plan, or one of its tasks, references an issue.
+    pub fn references_issue(&self, id: &ObjectId) -> bool {
+        self.related_issues.contains(id) || self.tasks.iter().any(|t| t.linked_issue.as_ref() == Some(id))
+    }
+
+    /// Check whether the plan, or one of its tasks, references a patch.
+    pub fn references_patch(&self, id: &ObjectId) -> bool {
+        self.related_patches.contains(id) || self.tasks.iter().any(|t| t.linked_patch.as_ref() == Some(id))
+    }
+
+    /// Check whether one of the plan's tasks is linked to a commit.
+    pub fn references_commit(&self, oid: &Oid) -> bool {
+        self.tasks.iter().any(|t| t.linked_commit.as_ref() == Some(oid))
+    }
+
+    /// Check whether the plan references a file, as a critical file or a file affected
+    /// by one of its tasks. A referenced directory matches every file under it.
+    pub fn references_file(&self, path: &str) -> bool {
+        self.critical_files
+            .iter()
+            .chain(self.tasks.iter().flat_map(|t| &t.affected_files))
+            .any(|f| path_matches(f, path))
+    }
+
     /// Get labels.
     pub fn labels(&self) -> impl Iterator<Item = &Label> {
         self.labels.iter()
@@ -274,18 +836,176 @@ impl Plan {
         self.thread.comments()
     }
 
-    /// Calculate completion percentage.
+    /// Sum of all task estimates.
+    pub fn total_estimate(&self) -> Estimate {
+        self.tasks.iter().filter_map(|t| t.estimate.as_ref()).sum()
+    }
+
+    /// Sum of the estimates of tasks that are not yet done.
+    pub fn remaining_estimate(&self) -> Estimate {
+        self.tasks
+            .iter()
+            .filter(|t| !self.is_task_done(&t.id))
+            .filter_map(|t| t.estimate.as_ref())
+            .sum()
+    }
+
+    /// Calculate completion percentage, weighted by task estimates.
+    ///
+    /// Tasks are weighted by estimated duration if any task has one, otherwise by
+    /// story points, otherwise equally. Tasks without an estimate are given the
+    /// average weight of the estimated tasks. A task with subtasks isn't weighted
+    /// itself: its work is that of its subtasks.
     pub fn completion_percentage(&self) -> f64 {
-        if self.tasks.is_empty() {
+        self.completion_of(|_| true)
+    }
+
+    /// Calculate the completion percentage of a milestone's tasks, weighted as in
+    /// [`Plan::completion_percentage`].
+    pub fn milestone_completion(&self, id: &MilestoneId) -> f64 {
+        self.completion_of(|t| t.milestone.as_ref() == Some(id))
+    }
+
+    /// Calculate the completion percentage of the tasks matching a predicate. Tasks
+    /// keep the weight they have within the whole plan.
+    fn completion_of(&self, include: impl Fn(&Task) -> bool) -> f64 {
+        let tasks = self
+            .leaf_weights()
+            .into_iter()
+            .filter(|(t, _)| include(*t))
+            .collect::<Vec<_>>();
+        if tasks.is_empty() {
             return 0.0;
         }
-        let done = self.tasks.iter().filter(|t| t.is_done()).count();
-        (done as f64 / self.tasks.len() as f64) * 100.0
+        let total: f64 = tasks.iter().map(|(_, w)| w).sum();
+        let done: f64 = tasks
+            .iter()
+            .filter(|(t, _)| self.is_leaf_done(t))
+            .map(|(_, w)| w)
+            .sum();
+
+        if total > 0.0 {
+            (done / total) * 100.0
+        } else {
+            let done = tasks.iter().filter(|(t, _)| self.is_leaf_done(t)).count();
+            (done as f64 / tasks.len() as f64) * 100.0
+        }
+    }
+
+    /// Tasks without subtasks, and their weight towards plan completion, in task order.
+    fn leaf_weights(&self) -> Vec<(&Task, f64)> {
+        let leaves = self
+            .tasks
+            .iter()
+            .filter(|t| self.children(&t.id).next().is_none())
+            .collect::<Vec<_>>();
+        let minutes = leaves
+            .iter()
+            .map(|t| t.estimate.as_ref().and_then(Estimate::minutes).map(|m| m as f64))
+            .collect::<Vec<_>>();
+        let points = leaves
+            .iter()
+            .map(|t| t.estimate.as_ref().and_then(Estimate::points).map(f64::from))
+            .collect::<Vec<_>>();
+
+        for weights in [minutes, points] {
+            let known = weights.iter().flatten().collect::<Vec<_>>();
+            if !known.is_empty() {
+                let average = known.iter().copied().sum::<f64>() / known.len() as f64;
+                return leaves.into_iter().zip(weights.into_iter().map(|w| w.unwrap_or(average))).collect();
+            }
+        }
+        leaves.into_iter().map(|t| (t, 1.0)).collect()
+    }
+
+    /// Check if a task without subtasks is done: it, or one of its ancestors, has a
+    /// linked commit.
+    fn is_leaf_done(&self, task: &Task) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut current = Some(task);
+
+        while let Some(task) = current {
+            if task.is_done() {
+                return true;
+            }
+            if !visited.insert(task.id) {
+                break;
+            }
+            current = task.parent.and_then(|p| self.task(&p));
+        }
+        false
     }
 
     /// Check if all tasks are complete.
     pub fn all_tasks_complete(&self) -> bool {
-        !self.tasks.is_empty() && self.tasks.iter().all(|t| t.is_done())
+        !self.tasks.is_empty() && self.tasks.iter().all(|t| self.is_task_done(&t.id))
+    }
+
+    /// Check if all tasks are complete, consulting the repository for the state of
+    /// linked patches. See [`Task::is_done_in`].
+    pub fn all_tasks_complete_in<R>(&self, repo: &R) -> Result<bool, Error>
+    where
+        R: ReadRepository + cob::Store,
+    {
+        for task in &self.tasks {
+            if !self.is_task_done_in(&task.id, repo)? {
+                return Ok(false);
+            }
+        }
+        Ok(!self.tasks.is_empty())
+    }
+
+    /// Get the status the plan's tasks imply, given its stored status.
+    ///
+    /// An approved plan is in progress once any of its tasks has a linked commit or
+    /// patch, and an approved or in-progress plan is completed once all of its tasks
+    /// are done. Otherwise, the stored status is returned. This doesn't consult
+    /// the repository; see [`Plan::effective_status_in`].
+    pub fn effective_status(&self) -> PlanStatus {
+        self.derive_status(self.all_tasks_complete())
+    }
+
+    /// Get the status the plan's tasks imply, counting tasks whose linked patch has
+    /// been merged as done. See [`Plan::effective_status`].
+    pub fn effective_status_in<R>(&self, repo: &R) -> Result<PlanStatus, Error>
+    where
+        R: ReadRepository + cob::Store,
+    {
+        Ok(self.derive_status(self.all_tasks_complete_in(repo)?))
+    }
+
+    fn derive_status(&self, complete: bool) -> PlanStatus {
+        let started = self
+            .tasks
+            .iter()
+            .any(|t| t.linked_commit.is_some() || t.linked_patch.is_some());
+
+        // Drafts need approval before work counts towards their status.
+        match self.status {
+            PlanStatus::Draft | PlanStatus::Archived | PlanStatus::Completed => self.status,
+            _ if complete => PlanStatus::Completed,
+            PlanStatus::Approved if started => PlanStatus::InProgress,
+            status => status,
+        }
+    }
+
+    /// Check that the plan can move to the given status: the transition must be
+    /// allowed by the state machine (see [`PlanStatus::can_transition_to`]), and
+    /// completing the plan requires all tasks to be done, unless `force` is set.
+    ///
+    /// Status changes are checked in full when they are made. When applied, only the
+    /// state machine is enforced, as tasks may have changed concurrently.
+    pub fn check_transition(&self, to: PlanStatus, force: bool) -> Result<(), TransitionError> {
+        if !self.status.can_transition_to(to) {
+            return Err(TransitionError::NotAllowed);
+        }
+        if to == PlanStatus::Completed && self.status != to && !force {
+            let pending = self.tasks.iter().filter(|t| !self.is_task_done(&t.id)).count();
+            if pending > 0 {
+                return Err(TransitionError::TasksNotDone(pending));
+            }
+        }
+        Ok(())
     }
 }
 
@@ -297,6 +1017,55 @@ impl Deref for Plan {
     }
 }
 
+/// Plans and tasks for tests.
+#[cfg(test)]
+pub(crate) mod test {
+    use std::str::FromStr;
+
+    use radicle::cob::common::Timestamp;
+    use radicle::cob::thread::{Comment, Thread};
+    use radicle::cob::ActorId;
+    use radicle::git::Oid;
+    use radicle::prelude::Did;
+
+    use super::{Plan, Task, TaskId};
+    use crate::estimate::Estimate;
+
+    /// Author of the plans and tasks built here.
+    pub(crate) fn author() -> ActorId {
+        *Did::from_str("did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK")
+            .unwrap()
+            .as_key()
+    }
+
+    /// An empty plan titled "Plan", created by the given change.
+    pub(crate) fn plan(id: Oid) -> Plan {
+        let comment = Comment::new(author(), String::new(), None, None, vec![], Timestamp::from_secs(0));
+        Plan::new("Plan".to_string(), String::new(), Thread::new(id, comment), author().into(), Timestamp::from_secs(0))
+    }
+
+    /// A task with the given subject.
+    pub(crate) fn task(id: TaskId, subject: impl ToString) -> Task {
+        Task::new(id, subject.to_string(), None, None, vec![], author(), Timestamp::from_secs(0))
+    }
+
+    impl Plan {
+        /// Add a task, returning it for further setup.
+        pub(crate) fn push_task(&mut self, id: TaskId, subject: impl ToString) -> &mut Task {
+            self.tasks.push(task(id, subject));
+            self.tasks.last_mut().expect("a task was just added")
+        }
+    }
+
+    impl Task {
+        /// Set the estimate, parsed leniently.
+        pub(crate) fn estimated(&mut self, estimate: &str) -> &mut Self {
+            self.estimate = Some(Estimate::lenient(estimate));
+            self
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -330,9 +1099,13 @@ mod tests {
             description: None,
             estimate: None,
             blocked_by: vec![],
+            parent: None,
+            milestone: None,
             affected_files: vec![],
+            assignees: BTreeSet::new(),
             linked_issue: None,
             linked_commit: None,
+            linked_patch: None,
             author,
             created_at: Timestamp::from_secs(0),
         };
@@ -343,4 +1116,110 @@ mod tests {
         task.linked_commit = Some(Oid::from_str("abcdef0000000000000000000000000000000001").unwrap());
         assert!(task.is_done());
     }
+
+    #[test]
+    fn test_task_is_done_with_merged_patch() {
+        use radicle::git::Oid;
+
+        let oid = |n: u8| Oid::from_str(&format!("{n:040x}")).unwrap();
+        let mut task = test::task(oid(1), "Test");
+        let patch = ObjectId::from(oid(2));
+
+        // Without links, the repository isn't consulted.
+        assert!(!task.is_done_with(|_| panic!("no patch to look up")).unwrap());
+
+        task.linked_patch = Some(patch);
+        assert!(!task.is_done_with(|_| Ok(false)).unwrap());
+        assert!(task.is_done_with(|p| Ok(p == &patch)).unwrap());
+        assert!(!task.is_done());
+        assert!(task.is_done_with(|_| Err(Error::MissingIdentity)).is_err());
+
+        // A linked commit is enough, whatever the state of the patch.
+        task.linked_commit = Some(oid(3));
+        assert!(task.is_done_with(|_| panic!("patch looked up")).unwrap());
+    }
+
+    #[test]
+    fn test_estimate_weighted_completion() {
+        use radicle::git::Oid;
+
+        let id = |n: u8| Oid::from_str(&format!("{n:040x}")).unwrap();
+        let mut plan = test::plan(id(0));
+        plan.push_task(id(1), "Task 1").estimated("3h");
+        plan.push_task(id(2), "Task 2").estimated("1h");
+        plan.push_task(id(3), "Task 3");
+        plan.tasks[0].linked_commit = Some(id(9));
+
+        assert_eq!(plan.total_estimate().minutes(), Some(240));
+        assert_eq!(plan.remaining_estimate().minutes(), Some(60));
+        // The unestimated task weighs as much as the 2h average.
+        assert_eq!(plan.completion_percentage(), 50.0);
+    }
+
+    #[test]
+    fn test_subtask_roll_up() {
+        use radicle::git::Oid;
+
+        let id = |n: u8| Oid::from_str(&format!("{n:040x}")).unwrap();
+        let mut plan = test::plan(id(0));
+        for n in 1..=4 {
+            plan.push_task(id(n), format!("Task {n}"));
+        }
+        // 4 is listed last, but shown under its parent, 1.
+        plan.tasks[1].parent = Some(id(1));
+        plan.tasks[2].parent = Some(id(2));
+        plan.tasks[3].parent = Some(id(1));
+
+        let tree = plan.task_tree().into_iter().map(|(d, t)| (d, t.id)).collect::<Vec<_>>();
+        assert_eq!(tree, vec![(0, id(1)), (1, id(2)), (2, id(3)), (1, id(4))]);
+        assert!(plan.is_ancestor(&id(1), &id(3)));
+        assert!(!plan.is_ancestor(&id(3), &id(1)));
+
+        plan.tasks[2].linked_commit = Some(id(9));
+        assert!(plan.is_task_done(&id(2)));
+        assert!(!plan.is_task_done(&id(1)));
+
+        plan.tasks[3].linked_commit = Some(id(9));
+        assert!(plan.is_task_done(&id(1)));
+        assert!(plan.all_tasks_complete());
+    }
+
+    #[test]
+    fn test_subtask_completion_weights_leaves() {
+        use radicle::git::Oid;
+
+        let id = |n: u8| Oid::from_str(&format!("{n:040x}")).unwrap();
+        let mut plan = test::plan(id(0));
+        plan.push_task(id(1), "Parent").estimated("10h");
+        plan.push_task(id(2), "Child A").estimated("2h").parent = Some(id(1));
+        plan.push_task(id(3), "Child B").estimated("2h").parent = Some(id(1));
+        plan.push_task(id(4), "Other").estimated("4h");
+
+        // The parent's own estimate doesn't add to its subtasks'.
+        plan.tasks[1].linked_commit = Some(id(9));
+        assert_eq!(plan.completion_percentage(), 25.0);
+
+        // Completing the parent directly completes its subtasks.
+        plan.tasks[0].linked_commit = Some(id(9));
+        assert_eq!(plan.completion_percentage(), 50.0);
+    }
+
+    #[test]
+    fn test_milestone_completion() {
+        use radicle::git::Oid;
+
+        let id = |n: u8| Oid::from_str(&format!("{n:040x}")).unwrap();
+        let mut plan = test::plan(id(0));
+        plan.milestones.push(Milestone::new(id(10), "Alpha".to_string(), None, test::author(), Timestamp::from_secs(0)));
+        for (n, estimate) in [(1, "3h"), (2, "1h"), (3, "4h")] {
+            plan.push_task(id(n), format!("Task {n}")).estimated(estimate).milestone = (n < 3).then(|| id(10));
+        }
+        plan.tasks[0].linked_commit = Some(id(9));
+
+        assert_eq!(plan.milestone_tasks(Some(&id(10))).count(), 2);
+        assert_eq!(plan.milestone_tasks(None).count(), 1);
+        assert_eq!(plan.milestone_completion(&id(10)), 75.0);
+        assert_eq!(plan.milestone_completion(&id(11)), 0.0);
+        assert_eq!(plan.completion_percentage(), 37.5);
+    }
 }
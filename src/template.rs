@@ -0,0 +1,165 @@
+//! Plan templates.
+//!
+//! A template is a [plan document](crate::document) stored in the repository under
+//! [`TEMPLATE_DIR`], eg. `.radicle/plans/feature-rollout.md`, that may contain
+//! `{{name}}` placeholders. Placeholders can have a default value, written
+//! `{{name|default}}`. Default labels and critical files are given as metadata:
+//!
+//! ```markdown
+//! # Roll out {{feature}}
+//!
+//! **Labels:** rollout, {{team|platform}}
+//! **Critical Files:** src/flags.rs
+//!
+//! ## Tasks
+//!
+//! - [ ] Design {{feature}} _(1d)_
+//! - [ ] Implement {{feature}} _(3d)_ <!-- blocked-by: 1 -->
+//! - [ ] Release {{feature}} <!-- blocked-by: 2 -->
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use thiserror::Error;
+
+use crate::document::{DocumentError, PlanDocument};
+
+/// Directory of plan templates, relative to the repository root.
+pub const TEMPLATE_DIR: &str = ".radicle/plans";
+
+/// Metadata key listing a template's default labels.
+pub const LABELS_KEY: &str = "Labels";
+
+/// Metadata key listing a template's default critical files.
+pub const CRITICAL_FILES_KEY: &str = "Critical Files";
+
+/// Error instantiating a plan template.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TemplateError {
+    /// A placeholder without a default has no value.
+    #[error("missing template variables: {}", .0.join(", "))]
+    MissingVariables(Vec<String>),
+    /// A placeholder is not closed.
+    #[error("unterminated placeholder at byte {0}")]
+    Unterminated(usize),
+    /// The instantiated template is not a valid plan document.
+    #[error(transparent)]
+    Document(#[from] DocumentError),
+}
+
+/// A plan template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanTemplate {
+    text: String,
+}
+
+impl PlanTemplate {
+    /// Create a template from its text.
+    pub fn new(text: impl ToString) -> Self {
+        Self {
+            text: text.to_string(),
+        }
+    }
+
+    /// Get the names of the template's placeholders that have no default value.
+    pub fn required(&self) -> Result<BTreeSet<String>, TemplateError> {
+        let mut required = BTreeSet::new();
+        self.substitute(|name, default| {
+            if default.is_none() {
+                required.insert(name.to_string());
+            }
+            Some(String::new())
+        })?;
+        Ok(required)
+    }
+
+    /// Instantiate the template with the given variables.
+    pub fn instantiate(&self, vars: &BTreeMap<String, String>) -> Result<PlanDocument, TemplateError> {
+        let mut missing = BTreeSet::new();
+        let text = self.substitute(|name, default| {
+            let value = vars.get(name).cloned().or_else(|| default.map(str::to_string));
+            if value.is_none() {
+                missing.insert(name.to_string());
+            }
+            value
+        })?;
+
+        if !missing.is_empty() {
+            return Err(TemplateError::MissingVariables(missing.into_iter().collect()));
+        }
+        Ok(PlanDocument::parse(&text)?)
+    }
+
+    /// Replace each `{{name}}` or `{{name|default}}` placeholder with a value.
+    fn substitute(
+        &self,
+        mut value: impl FnMut(&str, Option<&str>) -> Option<String>,
+    ) -> Result<String, TemplateError> {
+        let mut out = String::with_capacity(self.text.len());
+        let mut rest = self.text.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            let offset = self.text.len() - rest.len() + start;
+            let Some(end) = rest[start..].find("}}") else {
+                return Err(TemplateError::Unterminated(offset));
+            };
+            let placeholder = &rest[start + 2..start + end];
+            let (name, default) = match placeholder.split_once('|') {
+                Some((name, default)) => (name.trim(), Some(default.trim())),
+                None => (placeholder.trim(), None),
+            };
+
+            out.push_str(&rest[..start]);
+            out.push_str(&value(name, default).unwrap_or_default());
+            rest = &rest[start + end + 2..];
+        }
+        out.push_str(rest);
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "\
+# Roll out {{ feature }}
+
+**Labels:** rollout, {{team|platform}}
+**Critical Files:** src/flags.rs
+
+## Tasks
+
+- [ ] Design {{feature}} _(1d)_
+- [ ] Implement {{feature}} _(3d)_ <!-- blocked-by: 1 -->
+";
+
+    #[test]
+    fn test_instantiate() {
+        let template = PlanTemplate::new(TEMPLATE);
+        let vars = BTreeMap::from([("feature".to_string(), "dark mode".to_string())]);
+        let doc = template.instantiate(&vars).unwrap();
+
+        assert_eq!(doc.title, "Roll out dark mode");
+        assert_eq!(doc.metadata_list(LABELS_KEY), vec!["rollout", "platform"]);
+        assert_eq!(doc.metadata_list(CRITICAL_FILES_KEY), vec!["src/flags.rs"]);
+        assert_eq!(doc.tasks[1].subject, "Implement dark mode");
+        assert_eq!(doc.tasks[1].blocked_by, vec![1]);
+    }
+
+    #[test]
+    fn test_missing_variables() {
+        let template = PlanTemplate::new(TEMPLATE);
+
+        assert_eq!(template.required().unwrap(), BTreeSet::from(["feature".to_string()]));
+        assert_eq!(
+            template.instantiate(&BTreeMap::new()),
+            Err(TemplateError::MissingVariables(vec!["feature".to_string()]))
+        );
+        assert_eq!(
+            PlanTemplate::new("# {{oops").instantiate(&BTreeMap::new()),
+            Err(TemplateError::Unterminated(2))
+        );
+    }
+}
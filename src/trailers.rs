@@ -0,0 +1,264 @@
+//! Task trailers in commit messages.
+//!
+//! Commits can say which plan tasks they complete with trailers in the last paragraph
+//! of their message:
+//!
+//! ```text
+//! Add session middleware
+//!
+//! Plan-Task: 9f8e7d6
+//! Closes-Task: 1a2b3c4/5c4b3a2
+//! ```
+//!
+//! A trailer names a task by ID prefix, optionally qualified by a plan ID prefix, as
+//! `<plan>/<task>`. Walking a range of commits for these trailers gives the links
+//! between tasks and commits that [`PlanMut::sync_commits`](crate::PlanMut::sync_commits)
+//! records.
+//!
+//! Commits are walked in a local Git repository, usually the working copy, so that
+//! branch names resolve as they do for `git log`. Commits that haven't been pushed yet
+//! aren't in the Radicle storage, and [`Plan::verify_commits`] reports them as missing
+//! until they are.
+
+use radicle::git;
+use radicle::git::raw;
+
+use crate::{Plan, PlanId, TaskId};
+
+/// Trailer naming a task the commit works on.
+pub const PLAN_TASK: &str = "Plan-Task";
+
+/// Trailer naming a task the commit completes.
+pub const CLOSES_TASK: &str = "Closes-Task";
+
+/// Minimum length of plan and task ID prefixes in trailers.
+const MIN_PREFIX_LEN: usize = 7;
+
+/// A task trailer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskTrailer {
+    /// Plan ID prefix, if the task is qualified by plan.
+    pub plan: Option<String>,
+    /// Task ID prefix.
+    pub task: String,
+}
+
+impl TaskTrailer {
+    /// Parse the task trailers of a commit message.
+    pub fn parse(message: &str) -> Vec<Self> {
+        let paragraphs = message
+            .trim()
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>();
+        // The subject line is never a trailer block.
+        let Some(block) = paragraphs.last().filter(|_| paragraphs.len() > 1) else {
+            return Vec::new();
+        };
+
+        block
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                let key = key.trim();
+                if !key.eq_ignore_ascii_case(PLAN_TASK) && !key.eq_ignore_ascii_case(CLOSES_TASK) {
+                    return None;
+                }
+                let value = value.trim().to_lowercase();
+                let trailer = match value.split_once('/') {
+                    Some((plan, task)) => Self {
+                        plan: Some(plan.trim().to_string()),
+                        task: task.trim().to_string(),
+                    },
+                    None => Self {
+                        plan: None,
+                        task: value,
+                    },
+                };
+                Some(trailer)
+            })
+            .collect()
+    }
+
+    /// Resolve the trailer to a task of the given plan.
+    ///
+    /// Returns `None` if the trailer names another plan, or if its task prefix is too
+    /// short or doesn't match exactly one task of the plan.
+    pub fn resolve(&self, id: &PlanId, plan: &Plan) -> Option<TaskId> {
+        if !self.is_for(id) || !self.plan.as_deref().is_none_or(is_prefix) || !is_prefix(&self.task) {
+            return None;
+        }
+        let mut tasks = plan.tasks_by_prefix(&self.task);
+        match (tasks.next(), tasks.next()) {
+            (Some(task), None) => Some(task.id),
+            _ => None,
+        }
+    }
+
+    /// Whether the trailer may refer to the given plan, ie. it isn't qualified by
+    /// another plan.
+    pub fn is_for(&self, id: &PlanId) -> bool {
+        match &self.plan {
+            Some(prefix) => id.to_string().starts_with(prefix.as_str()),
+            None => true,
+        }
+    }
+}
+
+/// Task trailers found in a range of commits.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitTrailers {
+    /// Tasks and the commits they were completed in, in commit order. If several
+    /// commits name the same task, the most recent one is kept.
+    pub links: Vec<(TaskId, git::Oid)>,
+    /// Trailers for this plan that didn't resolve to one of its tasks.
+    pub unresolved: Vec<(git::Oid, TaskTrailer)>,
+}
+
+impl CommitTrailers {
+    /// Walk the commits of a revision range, eg. `main..feature` or `HEAD`, oldest
+    /// first, and resolve the task trailers that refer to the given plan.
+    pub fn walk(repo: &raw::Repository, revspec: &str, id: &PlanId, plan: &Plan) -> Result<Self, raw::Error> {
+        let spec = repo.revparse(revspec)?;
+        let mut walk = repo.revwalk()?;
+        walk.set_sorting(raw::Sort::TOPOLOGICAL | raw::Sort::REVERSE)?;
+
+        if spec.mode().contains(raw::RevparseMode::RANGE) {
+            if let Some(to) = spec.to() {
+                walk.push(to.peel_to_commit()?.id())?;
+            }
+            if let Some(from) = spec.from() {
+                walk.hide(from.peel_to_commit()?.id())?;
+            }
+        } else if let Some(from) = spec.from() {
+            walk.push(from.peel_to_commit()?.id())?;
+        }
+
+        let mut trailers = Self::default();
+        for oid in walk {
+            let commit = repo.find_commit(oid?)?;
+            let oid = git::Oid::from(commit.id());
+
+            for trailer in TaskTrailer::parse(commit.message().unwrap_or_default()) {
+                match trailer.resolve(id, plan) {
+                    Some(task) => {
+                        trailers.links.retain(|(t, _)| *t != task);
+                        trailers.links.push((task, oid));
+                    }
+                    None if trailer.is_for(id) => trailers.unresolved.push((oid, trailer)),
+                    None => {}
+                }
+            }
+        }
+        Ok(trailers)
+    }
+}
+
+/// Whether a string is a hex ID prefix of at least [`MIN_PREFIX_LEN`] characters.
+fn is_prefix(s: &str) -> bool {
+    s.len() >= MIN_PREFIX_LEN && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr;
+
+    use crate::state::test;
+
+    fn oid(s: &str) -> git::Oid {
+        git::Oid::from_str(&format!("{s:0<40}")).unwrap()
+    }
+
+    fn plan(tasks: &[&str]) -> (PlanId, Plan) {
+        let id = oid("aaaaaaa");
+        let mut plan = test::plan(id);
+        for task in tasks {
+            plan.push_task(oid(task), task);
+        }
+        (PlanId::from(id), plan)
+    }
+
+    fn trailer(plan: Option<&str>, task: &str) -> TaskTrailer {
+        TaskTrailer {
+            plan: plan.map(str::to_string),
+            task: task.to_string(),
+        }
+    }
+
+    /// Commit to the repository's HEAD, with an empty tree.
+    fn commit(repo: &raw::Repository, message: &str) -> git::Oid {
+        let sig = raw::Signature::now("Alice", "alice@example.com").unwrap();
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents = parent.iter().collect::<Vec<_>>();
+
+        git::Oid::from(repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).unwrap())
+    }
+
+    #[test]
+    fn test_resolve() {
+        let (id, plan) = plan(&["12345670", "12345671", "abcdef1"]);
+        let task = oid("abcdef1");
+
+        assert_eq!(trailer(None, "abcdef1").resolve(&id, &plan), Some(task));
+        assert_eq!(trailer(Some("aaaaaaa"), "abcdef1").resolve(&id, &plan), Some(task));
+        // Prefixes must be long enough, and match a single task.
+        assert_eq!(trailer(None, "abcdef").resolve(&id, &plan), None);
+        assert_eq!(trailer(Some("aaa"), "abcdef1").resolve(&id, &plan), None);
+        assert_eq!(trailer(None, "1234567").resolve(&id, &plan), None);
+        assert_eq!(trailer(None, "fedcba9").resolve(&id, &plan), None);
+        // Trailers for other plans are neither resolved nor reported.
+        let other = trailer(Some("bbbbbbb"), "abcdef1");
+        assert_eq!(other.resolve(&id, &plan), None);
+        assert!(!other.is_for(&id));
+    }
+
+    #[test]
+    fn test_walk() {
+        let (id, plan) = plan(&["abcdef1", "1234567"]);
+        let dir = tempfile::tempdir().unwrap();
+        let repo = raw::Repository::init(dir.path()).unwrap();
+
+        let first = commit(&repo, "Start\n\nPlan-Task: abcdef1\nPlan-Task: 1234567");
+        let second = commit(&repo, "Finish\n\nCloses-Task: aaaaaaa/abcdef1\nPlan-Task: 0000000");
+        commit(&repo, "Unrelated\n\nCloses-Task: bbbbbbb/abcdef1");
+
+        // The most recent commit naming a task wins.
+        let trailers = CommitTrailers::walk(&repo, "HEAD", &id, &plan).unwrap();
+        assert_eq!(trailers.links, vec![(oid("1234567"), first), (oid("abcdef1"), second)]);
+        assert_eq!(trailers.unresolved, vec![(second, trailer(None, "0000000"))]);
+
+        let trailers = CommitTrailers::walk(&repo, &format!("{first}..HEAD"), &id, &plan).unwrap();
+        assert_eq!(trailers.links, vec![(oid("abcdef1"), second)]);
+    }
+
+    #[test]
+    fn test_parse() {
+        let message = "\
+Add session middleware
+
+Closes-Task: 9f8e7d6 is not a trailer here.
+
+Plan-Task: 9F8E7D6
+closes-task: 1a2b3c4/5c4b3a2
+Signed-off-by: Alice <alice@example.com>
+";
+        assert_eq!(
+            TaskTrailer::parse(message),
+            vec![
+                TaskTrailer {
+                    plan: None,
+                    task: "9f8e7d6".to_string(),
+                },
+                TaskTrailer {
+                    plan: Some("1a2b3c4".to_string()),
+                    task: "5c4b3a2".to_string(),
+                },
+            ]
+        );
+        assert!(TaskTrailer::parse("Plan-Task: 9f8e7d6").is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+//! Verification of linked commits.
+//!
+//! A task is done once it is linked to a commit, but nothing stops that commit from
+//! being rebased away or never merged. Verifying a plan's commits checks each linked
+//! commit against the repository: whether it exists, and whether it is reachable from
+//! the project's canonical default branch.
+
+use serde::Serialize;
+
+use radicle::git::{self, raw};
+use radicle::prelude::ReadRepository;
+use radicle::storage::git::Repository;
+
+use crate::state::{Plan, TaskId};
+use crate::Error;
+
+/// State of a linked commit in the repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CommitStatus {
+    /// The commit is reachable from the default branch.
+    Merged,
+    /// The commit exists, but isn't reachable from the default branch.
+    Unmerged,
+    /// The commit isn't in the repository.
+    Missing,
+}
+
+/// A task's linked commit, and its state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitCheck {
+    /// Task the commit is linked to.
+    pub task: TaskId,
+    /// Linked commit.
+    pub commit: git::Oid,
+    /// State of the commit.
+    pub status: CommitStatus,
+}
+
+/// Report on the linked commits of a plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitReport {
+    /// Canonical default branch the commits were checked against.
+    pub branch: String,
+    /// Tip of the default branch.
+    pub head: git::Oid,
+    /// Linked commits, in task order.
+    pub commits: Vec<CommitCheck>,
+}
+
+impl CommitReport {
+    /// Get the linked commits that aren't merged into the default branch.
+    pub fn failures(&self) -> impl Iterator<Item = &CommitCheck> {
+        self.commits.iter().filter(|c| c.status != CommitStatus::Merged)
+    }
+
+    /// Whether every linked commit is merged into the default branch.
+    pub fn is_ok(&self) -> bool {
+        self.failures().next().is_none()
+    }
+}
+
+impl Plan {
+    /// Check the commits linked to this plan's tasks against the repository's
+    /// canonical default branch.
+    pub fn verify_commits(&self, repo: &Repository) -> Result<CommitReport, Error> {
+        let (branch, head) = repo.canonical_head()?;
+        let commits = self.check_commits(&repo.backend, raw::Oid::from(head))?;
+
+        Ok(CommitReport {
+            branch: branch.to_string(),
+            head,
+            commits,
+        })
+    }
+
+    /// Check the linked commits against the given tip, in task order.
+    fn check_commits(&self, repo: &raw::Repository, tip: raw::Oid) -> Result<Vec<CommitCheck>, raw::Error> {
+        let mut commits = Vec::new();
+
+        for task in &self.tasks {
+            let Some(commit) = task.linked_commit else {
+                continue;
+            };
+            let oid = raw::Oid::from(commit);
+            let status = match repo.find_commit(oid) {
+                Ok(_) if oid == tip || repo.graph_descendant_of(tip, oid)? => CommitStatus::Merged,
+                Ok(_) => CommitStatus::Unmerged,
+                Err(e) if e.code() == raw::ErrorCode::NotFound => CommitStatus::Missing,
+                Err(e) => return Err(e),
+            };
+            commits.push(CommitCheck {
+                task: task.id,
+                commit,
+                status,
+            });
+        }
+        Ok(commits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr;
+
+    use crate::state::test;
+
+    fn oid(n: u8) -> git::Oid {
+        git::Oid::from_str(&format!("{n:040x}")).unwrap()
+    }
+
+    /// A plan with one task per given commit, linked to it if there is one.
+    fn plan(commits: &[Option<git::Oid>]) -> Plan {
+        let mut plan = test::plan(oid(0));
+        for (i, commit) in commits.iter().enumerate() {
+            plan.push_task(oid(i as u8 + 1), format!("Task {i}")).linked_commit = *commit;
+        }
+        plan
+    }
+
+    /// Commit with an empty tree on top of the given parent, updating `HEAD` if asked to.
+    fn commit(repo: &raw::Repository, head: bool, parent: Option<git::Oid>) -> git::Oid {
+        let sig = raw::Signature::now("Alice", "alice@example.com").unwrap();
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+        let parent = parent.map(|p| repo.find_commit(p.into()).unwrap());
+        let parents = parent.iter().collect::<Vec<_>>();
+        let update = head.then_some("HEAD");
+
+        git::Oid::from(repo.commit(update, &sig, &sig, "Commit", &tree, &parents).unwrap())
+    }
+
+    #[test]
+    fn test_check_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = raw::Repository::init(dir.path()).unwrap();
+
+        let base = commit(&repo, true, None);
+        let tip = commit(&repo, true, Some(base));
+        let unmerged = commit(&repo, false, Some(base));
+        let missing = oid(0xff);
+
+        let plan = plan(&[Some(base), None, Some(tip), Some(unmerged), Some(missing)]);
+        let statuses = plan
+            .check_commits(&repo, tip.into())
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.task, c.commit, c.status))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            statuses,
+            vec![
+                (oid(1), base, CommitStatus::Merged),
+                (oid(3), tip, CommitStatus::Merged),
+                (oid(4), unmerged, CommitStatus::Unmerged),
+                (oid(5), missing, CommitStatus::Missing),
+            ]
+        );
+    }
+}